@@ -0,0 +1,44 @@
+use clap::builder::RangedU64ValueParser;
+use clap::{arg, command, value_parser};
+use ed25519::lib::dudect::{measure, Target, DEFAULT_THRESHOLD};
+
+fn main() {
+    let names: Vec<&str> = Target::ALL.iter().map(|target| target.name()).collect();
+    let matches = command!()
+        .about("check secret-dependent operations for timing leaks with Welch's t-test")
+        .arg(arg!(-t --target <TARGET> "operation to measure (default: all)").value_parser(names))
+        .arg(
+            arg!(-n --measurements <N> "number of measurements per operation")
+                .default_value("100000")
+                .value_parser(RangedU64ValueParser::<usize>::new().range(2..)),
+        )
+        .arg(
+            arg!(--threshold <T> "|t| above which a leak is reported (default: 4.5)")
+                .value_parser(value_parser!(f64)),
+        )
+        .get_matches();
+
+    let measurements = *matches.get_one::<usize>("measurements").unwrap();
+    let threshold = matches
+        .get_one::<f64>("threshold")
+        .copied()
+        .unwrap_or(DEFAULT_THRESHOLD);
+    let targets: Vec<Target> = match matches.get_one::<String>("target") {
+        Some(name) => Target::ALL
+            .into_iter()
+            .filter(|target| target.name() == name)
+            .collect(),
+        None => Target::ALL.to_vec(),
+    };
+
+    let mut leaks = false;
+    for target in targets {
+        let report = measure(target, measurements, threshold);
+        println!("{report}");
+        leaks |= report.leaks();
+    }
+
+    if leaks {
+        std::process::exit(1);
+    }
+}
@@ -1,19 +1,42 @@
 use clap::{arg, command, value_parser};
-use ed25519::lib::keygen::generate_key_pair;
-use ed25519::lib::Key;
+use ed25519::lib::ed448;
+use ed25519::lib::keygen::gen_public;
+use ed25519::lib::keys::SigningKey;
+use ed25519::lib::secret::SecretBuffer;
 use std::fs;
 use std::io::Write;
 
-fn write_key(filename: &str, key: Key) -> Result<(), std::io::Error> {
+fn write_key(filename: &str, key: &[u8]) -> Result<(), std::io::Error> {
     let mut key_file = fs::OpenOptions::new()
         .create(true)
         .write(true)
+        .truncate(true)
         .open(filename)?;
 
-    key_file.write_all(&key)?;
+    key_file.write_all(key)?;
     Ok(())
 }
 
+fn write_keys(prefix: &str, private: &[u8], public: &[u8]) {
+    for (filename, key) in [
+        (format!("{prefix}.sk"), private),
+        (format!("{prefix}.pk"), public),
+    ] {
+        write_key(&filename, key).unwrap_or_else(|_| {
+            eprintln!("Failed writing key in {filename}");
+            std::process::exit(1)
+        });
+    }
+}
+
+// wiped when dropped
+fn new_private<const N: usize>(lock: bool) -> SecretBuffer<N> {
+    SecretBuffer::random(lock).unwrap_or_else(|e| {
+        eprintln!("Failed locking the secret key in memory: {e}");
+        std::process::exit(1)
+    })
+}
+
 fn main() {
     let matches = command!()
         .arg(
@@ -21,19 +44,25 @@ fn main() {
                 .required(true)
                 .value_parser(value_parser!(String)),
         )
+        .arg(
+            arg!(--algorithm <ALGORITHM> "signature scheme of the key pair")
+                .value_parser(["ed25519", "ed448"])
+                .default_value("ed25519"),
+        )
+        .arg(arg!(--mlock "lock the secret key in memory so that it is never swapped to disk"))
         .get_matches();
 
     let prefix = matches.get_one::<String>("PREFIX").unwrap();
+    let lock = matches.get_flag("mlock");
 
-    let (private, public) = generate_key_pair();
-
-    for (filename, key) in [
-        (format!("{prefix}.sk"), private),
-        (format!("{prefix}.pk"), public),
-    ] {
-        write_key(&filename, key).unwrap_or_else(|_| {
-            eprintln!("Failed writing key in {filename}");
-            std::process::exit(1)
-        });
+    match matches.get_one::<String>("algorithm").unwrap().as_str() {
+        "ed448" => {
+            let private = new_private(lock);
+            write_keys(prefix, &*private, &ed448::gen_public(&private));
+        }
+        _ => {
+            let private = SigningKey::from(new_private(lock));
+            write_keys(prefix, private.as_bytes(), gen_public(&private).as_bytes());
+        }
     }
 }
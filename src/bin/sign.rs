@@ -1,8 +1,29 @@
 use clap::{arg, command, value_parser};
-use ed25519::lib::sign::sign;
-use ed25519::lib::Key;
+use ed25519::lib::ed448;
+use ed25519::lib::keys::{ExpandedSecretKey, SigningKey};
+use ed25519::lib::secret::SecretBuffer;
+use ed25519::lib::Error;
 use std::fs;
-use std::io::Write;
+use std::io::{self, Write};
+
+// the key is read in place into a buffer which is wiped when dropped
+fn read_private<const N: usize>(prefix: &str, lock: bool) -> SecretBuffer<N> {
+    let filename = format!("{prefix}.sk");
+    SecretBuffer::from_file(&filename, lock).unwrap_or_else(|e| {
+        match e.kind() {
+            io::ErrorKind::InvalidData => eprintln!("Invalid key in {filename}"),
+            _ => eprintln!("Failed reading {filename}: {e}"),
+        }
+        std::process::exit(1)
+    })
+}
+
+fn open_message(datafile: &str) -> fs::File {
+    fs::File::open(datafile).unwrap_or_else(|_| {
+        eprintln!("Failed reading {datafile}");
+        std::process::exit(1)
+    })
+}
 
 fn main() {
     let matches = command!()
@@ -21,39 +42,66 @@ fn main() {
                 .required(true)
                 .value_parser(value_parser!(String)),
         )
+        .arg(
+            arg!(--algorithm <ALGORITHM> "signature scheme of the key")
+                .value_parser(["ed25519", "ed448"])
+                .default_value("ed25519"),
+        )
+        .arg(arg!(--context <CONTEXT> "context string of at most 255 bytes (Ed25519ctx, or Ed448 with a context)"))
+        .arg(arg!(--prehash "sign the SHA-512 hash of the message (Ed25519ph)"))
+        .arg(arg!(--mlock "lock the secret key in memory so that it is never swapped to disk"))
         .get_matches();
 
     let prefix = matches.get_one::<String>("PREFIX").unwrap();
     let datafile = matches.get_one::<String>("DATAFILE").unwrap();
     let sigfilename = matches.get_one::<String>("SIGFILE").unwrap();
+    let algorithm = matches.get_one::<String>("algorithm").unwrap();
+    let context = matches.get_one::<String>("context").map(String::as_bytes);
+    let prehash = matches.get_flag("prehash");
+    let lock = matches.get_flag("mlock");
 
-    let private: Key = fs::read(format!("{prefix}.sk"))
-        .unwrap_or_else(|_| {
-            eprintln!("Failed reading {prefix}.sk");
-            std::process::exit(1)
-        })
-        .try_into()
-        .unwrap_or_else(|_| {
-            eprintln!("Invalid key in {prefix}.sk");
-            std::process::exit(1)
-        });
+    if prehash && algorithm == "ed448" {
+        eprintln!("--prehash is only supported with ed25519");
+        std::process::exit(1)
+    }
 
-    let message = fs::read(datafile).unwrap_or_else(|_| {
-        eprintln!("Failed reading {datafile}");
+    // the message is streamed from the file, so that its size does not matter
+    let message = open_message(datafile);
+    let signature = match (algorithm.as_str(), context) {
+        ("ed448", context) => ed448::sign_with_context_reader(
+            &read_private(prefix, lock),
+            context.unwrap_or(&[]),
+            message,
+        )
+        .map(|signature| signature.to_vec()),
+        (_, context) => {
+            let private = ExpandedSecretKey::from(&SigningKey::from(read_private(prefix, lock)));
+            match context {
+                _ if prehash => private.sign_prehashed_reader(context.unwrap_or(&[]), message),
+                Some(context) => private.sign_with_context_reader(context, message),
+                None => private.sign_reader(message),
+            }
+            .map(|signature| signature.to_bytes().to_vec())
+        }
+    }
+    .unwrap_or_else(|e| {
+        match e {
+            Error::ContextTooLong => eprintln!("The context is longer than 255 bytes"),
+            _ => eprintln!("Failed reading {datafile}: {e}"),
+        }
         std::process::exit(1)
     });
 
     let mut sigfile = fs::OpenOptions::new()
         .create(true)
         .write(true)
+        .truncate(true)
         .open(sigfilename)
         .unwrap_or_else(|_| {
             eprintln!("Failed opening {sigfilename}");
             std::process::exit(1)
         });
 
-    let signature = sign(private, &message);
-
     sigfile.write_all(&signature).unwrap_or_else(|_| {
         eprintln!("Failed writing signature in {sigfilename}");
         std::process::exit(1)
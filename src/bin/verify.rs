@@ -1,8 +1,27 @@
 use clap::{arg, command, value_parser};
-use ed25519::lib::verify::verify;
-use ed25519::lib::{Key, Signature};
+use ed25519::lib::ed448;
+use ed25519::lib::keys::{Signature, VerifyingKey};
+use ed25519::lib::verify::{
+    verify_prehashed_reader, verify_reader, verify_with_context_reader, VerificationPolicy,
+};
+use ed25519::lib::{Error, Key};
 use std::fs;
 
+fn read_file(filename: &str) -> Vec<u8> {
+    fs::read(filename).unwrap_or_else(|_| {
+        eprintln!("Failed reading {filename}");
+        std::process::exit(1)
+    })
+}
+
+// fails if bytes does not have the length of T
+fn parse<T: TryFrom<Vec<u8>>>(bytes: Vec<u8>, what: &str, filename: &str) -> T {
+    bytes.try_into().unwrap_or_else(|_| {
+        eprintln!("Invalid {what} in {filename}");
+        std::process::exit(1)
+    })
+}
+
 fn main() {
     let matches = command!()
         .arg(
@@ -20,42 +39,111 @@ fn main() {
                 .required(true)
                 .value_parser(value_parser!(String)),
         )
+        .arg(
+            arg!(--algorithm <ALGORITHM> "signature scheme of the key")
+                .value_parser(["ed25519", "ed448"])
+                .default_value("ed25519"),
+        )
+        .arg(arg!(--context <CONTEXT> "context string the message was signed with"))
+        .arg(arg!(--prehash "verify a signature of the SHA-512 hash of the message (Ed25519ph)"))
+        .arg(
+            arg!(--policy <POLICY> "which Ed25519 signatures are valid (strict by default)")
+                .value_parser(["strict", "cofactored", "zip215", "no-small-order-keys"]),
+        )
+        .arg(arg!(--explain "print why an Ed25519 signature is rejected"))
         .get_matches();
 
     let pkfile = matches.get_one::<String>("PKFILE").unwrap();
     let datafile = matches.get_one::<String>("DATAFILE").unwrap();
     let sigfile = matches.get_one::<String>("SIGFILE").unwrap();
 
-    let public: Key = fs::read(pkfile)
-        .unwrap_or_else(|_| {
-            eprintln!("Failed reading {pkfile}");
-            std::process::exit(1)
-        })
-        .try_into()
-        .unwrap_or_else(|_| {
-            eprintln!("Invalid key in {pkfile}");
-            std::process::exit(1)
-        });
+    let algorithm = matches.get_one::<String>("algorithm").unwrap();
+    let context = matches.get_one::<String>("context").map(String::as_bytes);
+    let prehash = matches.get_flag("prehash");
+
+    let policy = matches.get_one::<String>("policy");
+    let explain = matches.get_flag("explain");
+
+    if prehash && algorithm == "ed448" {
+        eprintln!("--prehash is only supported with ed25519");
+        std::process::exit(1)
+    }
+    if policy.is_some() && algorithm == "ed448" {
+        eprintln!("--policy is only supported with ed25519");
+        std::process::exit(1)
+    }
+    if explain && algorithm == "ed448" {
+        eprintln!("--explain is only supported with ed25519");
+        std::process::exit(1)
+    }
+    let policy = match policy.map(String::as_str) {
+        Some("cofactored") => VerificationPolicy::Cofactored,
+        Some("zip215") => VerificationPolicy::Zip215,
+        Some("no-small-order-keys") => VerificationPolicy::NoSmallOrderKeys,
+        _ => VerificationPolicy::Strict,
+    };
 
-    let message = fs::read(datafile).unwrap_or_else(|_| {
+    let public = read_file(pkfile);
+    let signature = read_file(sigfile);
+
+    // the message is streamed from the file, so that its size does not matter
+    let message = fs::File::open(datafile).unwrap_or_else(|_| {
         eprintln!("Failed reading {datafile}");
         std::process::exit(1)
     });
 
-    let signature: Signature = fs::read(sigfile)
-        .unwrap_or_else(|_| {
-            eprintln!("Failed reading {sigfile}");
+    // Err holds why the signature is rejected, which is only known for Ed25519
+    let verdict: Result<(), Option<String>> = match (algorithm.as_str(), context) {
+        ("ed448", context) => ed448::verify_with_context_reader(
+            parse(public, "key", pkfile),
+            context.unwrap_or(&[]),
+            message,
+            parse(signature, "signature", sigfile),
+        )
+        .unwrap_or_else(|e| {
+            eprintln!("Failed reading {datafile}: {e}");
             std::process::exit(1)
         })
-        .try_into()
-        .unwrap_or_else(|_| {
-            eprintln!("Invalid signature in {sigfile}");
-            std::process::exit(1)
-        });
+        .then_some(())
+        .ok_or(None),
+        (_, context) => {
+            let public: Key = parse(public, "key", pkfile);
+            let signature: [u8; 64] = parse(signature, "signature", sigfile);
+            // a key or signature which cannot be decoded is rejected like an invalid signature
+            let public =
+                VerifyingKey::from_bytes(&public).map_err(|e| format!("invalid public key: {e}"));
+            let signature =
+                Signature::from_bytes(&signature).map_err(|e| format!("invalid signature: {e}"));
+            match (public, signature) {
+                (Ok(public), Ok(signature)) => match context {
+                    _ if prehash => verify_prehashed_reader(
+                        &public,
+                        context.unwrap_or(&[]),
+                        message,
+                        &signature,
+                        policy,
+                    ),
+                    Some(context) => {
+                        verify_with_context_reader(&public, context, message, &signature, policy)
+                    }
+                    None => verify_reader(&public, message, &signature, policy),
+                }
+                .map_err(|e| {
+                    if let Error::Io(_) = e {
+                        eprintln!("Failed reading {datafile}: {e}");
+                        std::process::exit(1)
+                    }
+                    e.to_string()
+                }),
+                (Err(e), _) | (_, Err(e)) => Err(e),
+            }
+            .map_err(Some)
+        }
+    };
 
-    if verify(public, &message, signature) {
-        println!("ACCEPT");
-    } else {
-        println!("REJECT");
+    match verdict {
+        Ok(()) => println!("ACCEPT"),
+        Err(Some(reason)) if explain => println!("REJECT: {reason}"),
+        Err(_) => println!("REJECT"),
     }
 }
@@ -0,0 +1,174 @@
+use clap::{arg, command, value_parser, Command};
+use ed25519::lib::secret::SecretBuffer;
+use ed25519::lib::x25519::{gen_public, private_from_ed25519, public_from_ed25519, shared_secret};
+use ed25519::lib::Key;
+use std::fs;
+use std::io::{self, Write};
+use zeroize::Zeroizing;
+
+fn read_key(filename: &str) -> Key {
+    fs::read(filename)
+        .unwrap_or_else(|_| {
+            eprintln!("Failed reading {filename}");
+            std::process::exit(1)
+        })
+        .try_into()
+        .unwrap_or_else(|_| {
+            eprintln!("Invalid key in {filename}");
+            std::process::exit(1)
+        })
+}
+
+// the key is read in place into a buffer which is wiped on drop
+fn read_secret_key(filename: &str, lock: bool) -> SecretBuffer {
+    SecretBuffer::from_file(filename, lock).unwrap_or_else(|e| {
+        match e.kind() {
+            io::ErrorKind::InvalidData => eprintln!("Invalid key in {filename}"),
+            _ => eprintln!("Failed reading {filename}: {e}"),
+        }
+        std::process::exit(1)
+    })
+}
+
+fn new_secret_buffer(lock: bool) -> SecretBuffer {
+    SecretBuffer::new(lock).unwrap_or_else(|e| {
+        eprintln!("Failed locking the secret key in memory: {e}");
+        std::process::exit(1)
+    })
+}
+
+fn write_key(filename: &str, key: &Key) {
+    fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(filename)
+        .and_then(|mut file| file.write_all(key))
+        .unwrap_or_else(|_| {
+            eprintln!("Failed writing key in {filename}");
+            std::process::exit(1)
+        });
+}
+
+fn main() {
+    let matches = command!()
+        .subcommand_required(true)
+        .arg(
+            arg!(--mlock "lock the secret keys in memory so that they are never swapped to disk")
+                .global(true),
+        )
+        .subcommand(
+            Command::new("keygen")
+                .about("generate a X25519 key pair in {prefix}.sk and {prefix}.pk")
+                .arg(
+                    arg!([PREFIX] "prefix used in the keyfiles filenames")
+                        .required(true)
+                        .value_parser(value_parser!(String)),
+                ),
+        )
+        .subcommand(
+            Command::new("exchange")
+                .about("compute the shared secret with a peer")
+                .arg(
+                    arg!([SKFILE] "file containing the local secret key")
+                        .required(true)
+                        .value_parser(value_parser!(String)),
+                )
+                .arg(
+                    arg!([PKFILE] "file containing the peer public key")
+                        .required(true)
+                        .value_parser(value_parser!(String)),
+                )
+                .arg(
+                    arg!([SHAREDFILE] "file where to write the shared secret")
+                        .required(true)
+                        .value_parser(value_parser!(String)),
+                ),
+        )
+        .subcommand(
+            Command::new("pk-from-ed25519")
+                .about("convert an Ed25519 public key to a X25519 public key, like libsodium")
+                .arg(
+                    arg!([INFILE] "file containing the Ed25519 public key")
+                        .required(true)
+                        .value_parser(value_parser!(String)),
+                )
+                .arg(
+                    arg!([OUTFILE] "file where to write the X25519 public key")
+                        .required(true)
+                        .value_parser(value_parser!(String)),
+                ),
+        )
+        .subcommand(
+            Command::new("sk-from-ed25519")
+                .about("convert an Ed25519 secret key to a X25519 secret key, like libsodium")
+                .arg(
+                    arg!([INFILE] "file containing the Ed25519 seed, or libsodium secret key")
+                        .required(true)
+                        .value_parser(value_parser!(String)),
+                )
+                .arg(
+                    arg!([OUTFILE] "file where to write the X25519 secret key")
+                        .required(true)
+                        .value_parser(value_parser!(String)),
+                ),
+        )
+        .get_matches();
+
+    let lock = matches.get_flag("mlock");
+
+    match matches.subcommand() {
+        Some(("keygen", matches)) => {
+            let prefix = matches.get_one::<String>("PREFIX").unwrap();
+            let private = SecretBuffer::random(lock).unwrap_or_else(|e| {
+                eprintln!("Failed locking the secret key in memory: {e}");
+                std::process::exit(1)
+            });
+            let public = gen_public(&private);
+            write_key(&format!("{prefix}.sk"), &private);
+            write_key(&format!("{prefix}.pk"), &public);
+        }
+        Some(("exchange", matches)) => {
+            let skfile = matches.get_one::<String>("SKFILE").unwrap();
+            let pkfile = matches.get_one::<String>("PKFILE").unwrap();
+            let sharedfile = matches.get_one::<String>("SHAREDFILE").unwrap();
+
+            let private = read_secret_key(skfile, lock);
+            let public = read_key(pkfile);
+            let shared = shared_secret(&private, public).unwrap_or_else(|_| {
+                eprintln!("Invalid public key in {pkfile}: the shared secret is zero");
+                std::process::exit(1)
+            });
+            write_key(sharedfile, &shared);
+        }
+        Some(("pk-from-ed25519", matches)) => {
+            let infile = matches.get_one::<String>("INFILE").unwrap();
+            let outfile = matches.get_one::<String>("OUTFILE").unwrap();
+
+            let public = public_from_ed25519(read_key(infile)).unwrap_or_else(|_| {
+                eprintln!("Invalid Ed25519 public key in {infile}");
+                std::process::exit(1)
+            });
+            write_key(outfile, &public);
+        }
+        Some(("sk-from-ed25519", matches)) => {
+            let infile = matches.get_one::<String>("INFILE").unwrap();
+            let outfile = matches.get_one::<String>("OUTFILE").unwrap();
+
+            // libsodium secret keys are the seed followed by the public key
+            let secret = Zeroizing::new(fs::read(infile).unwrap_or_else(|_| {
+                eprintln!("Failed reading {infile}");
+                std::process::exit(1)
+            }));
+            if secret.len() != 32 && secret.len() != 64 {
+                eprintln!("Invalid key in {infile}");
+                std::process::exit(1)
+            }
+            let mut private = new_secret_buffer(lock);
+            private.copy_from_slice(&secret[..32]);
+            drop(secret);
+            write_key(outfile, &private_from_ed25519(&private));
+        }
+        _ => unreachable!(),
+    }
+}
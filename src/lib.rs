@@ -1,14 +1,35 @@
 pub mod lib {
     pub type Key = [u8; 32];
-    pub type Signature = [u8; 64];
+    pub type Ed448Key = [u8; 57];
+    pub type Ed448Signature = [u8; 114];
+    pub type X448Key = [u8; 56];
 
-    mod arithmetic {
-        mod montgomery;
+    pub use error::Error;
+
+    pub mod arithmetic {
+        mod basepoint_table;
+        pub mod curve;
+        pub mod field;
+        pub(crate) mod field448;
+        pub mod hash_to_curve;
+        pub mod montgomery;
+        mod multiscalar;
         pub mod proj_edwards;
-        mod proj_montgomery;
-        mod xline_proj_montgomery;
+        pub mod proj_edwards448;
+        pub mod proj_montgomery;
+        pub mod ristretto;
+        pub mod scalar;
+        pub mod scalar448;
+        pub(crate) mod xline_proj_montgomery;
     }
+    pub mod dudect;
+    pub mod ed448;
+    pub mod error;
     pub mod keygen;
+    pub mod keys;
+    pub mod secret;
     pub mod sign;
     pub mod verify;
+    pub mod x25519;
+    pub mod x448;
 }
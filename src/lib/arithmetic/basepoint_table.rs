@@ -0,0 +1,113 @@
+use super::curve::{Curve25519, CurveParams};
+use super::field::FieldElement;
+use super::proj_edwards::ProjEdPoint;
+use std::sync::OnceLock;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+/// affine point in the (y + x, y - x, 2dxy) representation, which saves a few multiplications
+/// when added to a `ProjEdPoint`
+#[derive(Clone, Copy, Debug)]
+pub struct AffineNielsPoint {
+    pub(crate) y_plus_x: FieldElement,
+    pub(crate) y_minus_x: FieldElement,
+    pub(crate) xy2d: FieldElement,
+}
+
+impl ConditionallySelectable for AffineNielsPoint {
+    /// return b if choice is 1 and a otherwise, without branching on choice
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self {
+            y_plus_x: FieldElement::conditional_select(&a.y_plus_x, &b.y_plus_x, choice),
+            y_minus_x: FieldElement::conditional_select(&a.y_minus_x, &b.y_minus_x, choice),
+            xy2d: FieldElement::conditional_select(&a.xy2d, &b.xy2d, choice),
+        }
+    }
+}
+
+impl AffineNielsPoint {
+    const IDENTITY: Self = Self {
+        y_plus_x: FieldElement::ONE,
+        y_minus_x: FieldElement::ONE,
+        xy2d: FieldElement::ZERO,
+    };
+}
+
+// -(x, y) = (-x, y)
+impl std::ops::Neg for AffineNielsPoint {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            y_plus_x: self.y_minus_x,
+            y_minus_x: self.y_plus_x,
+            xy2d: -self.xy2d,
+        }
+    }
+}
+
+impl From<&ProjEdPoint> for AffineNielsPoint {
+    fn from(point: &ProjEdPoint) -> Self {
+        let z_inv = point.z().invert();
+        let x = *point.x() * z_inv;
+        let y = *point.y() * z_inv;
+        Self {
+            y_plus_x: y + x,
+            y_minus_x: y - x,
+            xy2d: x * y * (Curve25519::D + Curve25519::D),
+        }
+    }
+}
+
+/// table[i][j] = (j + 1) * 16^i * B, for 0 <= i < 64 and 0 <= j < 8
+pub struct BasepointTable([[AffineNielsPoint; 8]; 64]);
+
+impl BasepointTable {
+    fn new() -> Self {
+        let mut table = [[AffineNielsPoint::IDENTITY; 8]; 64];
+        let mut p = ProjEdPoint::basepoint();
+        for row in &mut table {
+            let mut multiple = p;
+            for entry in row.iter_mut() {
+                *entry = AffineNielsPoint::from(&multiple);
+                multiple += &p;
+            }
+            // p = 16 * p
+            p = p.double().double().double().double();
+        }
+        Self(table)
+    }
+
+    /// select digit * 16^i * B from row i in constant time, for -8 <= digit <= 8
+    pub(crate) fn select(&self, i: usize, digit: i8) -> AffineNielsPoint {
+        let negative = (digit as u8) >> 7;
+        let abs = ((digit as u8) ^ 0u8.wrapping_sub(negative)).wrapping_add(negative);
+
+        let mut point = AffineNielsPoint::IDENTITY;
+        for (j, entry) in (1u8..).zip(&self.0[i]) {
+            point.conditional_assign(entry, abs.ct_eq(&j));
+        }
+        AffineNielsPoint::conditional_select(&point, &-point, Choice::from(negative))
+    }
+}
+
+/// table of multiples of the base point, computed on first use
+pub fn basepoint_table() -> &'static BasepointTable {
+    static TABLE: OnceLock<BasepointTable> = OnceLock::new();
+    TABLE.get_or_init(BasepointTable::new)
+}
+
+/// odd multiples B, 3B, 5B, ..., 127B of the base point, for width-8 NAF, computed on first use
+pub fn basepoint_odd_multiples() -> &'static [AffineNielsPoint; 64] {
+    static TABLE: OnceLock<[AffineNielsPoint; 64]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = [AffineNielsPoint::IDENTITY; 64];
+        let b: ProjEdPoint = ProjEdPoint::basepoint();
+        let b2 = b.double();
+        let mut multiple = b;
+        for entry in &mut table {
+            *entry = AffineNielsPoint::from(&multiple);
+            multiple += &b2;
+        }
+        table
+    })
+}
@@ -0,0 +1,181 @@
+use super::field::FieldElement;
+use std::fmt::Debug;
+
+/// constants of a Montgomery curve B v^2 = u^3 + A u^2 + u over GF(2^255 - 19), together with
+/// the birationally equivalent twisted Edwards curve -x^2 + y^2 = 1 + d x^2 y^2
+///
+/// the maps are u = (1 + y) / (1 - y), v = sqrt(-(A + 2) / B) u / x, so d = -(A - 2) / (A + 2)
+///
+/// the Edwards formulas of `ProjEdPoint` assume a = -1, i.e. that -(A + 2) / B is a square, and
+/// they are complete when d is not a square
+///
+/// the trait can be implemented outside the crate, e.g. for a copy of curve25519:
+///
+/// ```
+/// use ed25519::lib::arithmetic::curve::{Curve25519, CurveParams};
+/// use ed25519::lib::arithmetic::field::FieldElement;
+/// use ed25519::lib::arithmetic::proj_edwards::ProjEdPoint;
+///
+/// #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// struct MyCurve;
+///
+/// impl CurveParams for MyCurve {
+///     const A: FieldElement = FieldElement::from_limbs([486_662, 0, 0, 0, 0]);
+///     const B: FieldElement = FieldElement::ONE;
+///     const D: FieldElement = Curve25519::D;
+///     const ROOT_MINUS_A_MINUS_2: FieldElement = Curve25519::ROOT_MINUS_A_MINUS_2;
+///     const BASEPOINT_X: FieldElement = Curve25519::BASEPOINT_X;
+///     const BASEPOINT_Y: FieldElement = Curve25519::BASEPOINT_Y;
+/// }
+///
+/// let mut b = ProjEdPoint::<MyCurve>::generic_basepoint();
+/// assert_eq!(b.double(), b + &b);
+/// assert_eq!(b.encode(), ProjEdPoint::basepoint().encode());
+/// ```
+pub trait CurveParams: Clone + Copy + Debug + PartialEq + Eq + 'static {
+    /// coefficient A of the Montgomery curve
+    const A: FieldElement;
+    /// coefficient B of the Montgomery curve
+    const B: FieldElement;
+    /// coefficient d of the Edwards curve
+    const D: FieldElement;
+    /// sqrt(-(A + 2) / B), used by the birational maps
+    const ROOT_MINUS_A_MINUS_2: FieldElement;
+    /// affine coordinates of the Edwards base point
+    const BASEPOINT_X: FieldElement;
+    const BASEPOINT_Y: FieldElement;
+}
+
+/// curve25519 (A = 486662, B = 1) and edwards25519 (RFC 7748, section 4.1)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Curve25519;
+
+impl CurveParams for Curve25519 {
+    const A: FieldElement = FieldElement::from_limbs([486_662, 0, 0, 0, 0]);
+    const B: FieldElement = FieldElement::ONE;
+    const D: FieldElement = FieldElement::from_limbs([
+        929_955_233_495_203,
+        466_365_720_129_213,
+        1_662_059_464_998_953,
+        2_033_849_074_728_123,
+        1_442_794_654_840_575,
+    ]);
+    const ROOT_MINUS_A_MINUS_2: FieldElement = FieldElement::from_limbs([
+        1_693_982_333_959_686,
+        608_509_411_481_997,
+        2_235_573_344_831_311,
+        947_681_270_984_193,
+        266_558_006_233_600,
+    ]);
+    const BASEPOINT_X: FieldElement = FieldElement::from_limbs([
+        1_738_742_601_995_546,
+        1_146_398_526_822_698,
+        2_070_867_633_025_821,
+        562_264_141_797_630,
+        587_772_402_128_613,
+    ]);
+    const BASEPOINT_Y: FieldElement = FieldElement::from_limbs([
+        1_801_439_850_948_184,
+        1_351_079_888_211_148,
+        450_359_962_737_049,
+        900_719_925_474_099,
+        1_801_439_850_948_198,
+    ]);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lib::arithmetic::montgomery::MPoint;
+    use crate::lib::arithmetic::proj_edwards::ProjEdPoint;
+    use crate::lib::arithmetic::proj_montgomery::ProjMPoint;
+    use crate::lib::arithmetic::xline_proj_montgomery::ladder;
+
+    /// v^2 = u^3 + 10 u^2 + u, with the base point u = 6, computed with Python
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    struct TestCurve;
+
+    impl CurveParams for TestCurve {
+        const A: FieldElement = FieldElement::from_limbs([10, 0, 0, 0, 0]);
+        const B: FieldElement = FieldElement::ONE;
+        const D: FieldElement = FieldElement::from_limbs([
+            1_501_199_875_790_152,
+            750_599_937_895_082,
+            1_501_199_875_790_165,
+            750_599_937_895_082,
+            1_501_199_875_790_165,
+        ]);
+        const ROOT_MINUS_A_MINUS_2: FieldElement = FieldElement::from_limbs([
+            1_299_115_528_710_904,
+            1_090_040_643_431_100,
+            636_262_601_872_677,
+            1_288_958_099_089_013,
+            555_053_426_763_430,
+        ]);
+        const BASEPOINT_X: FieldElement = FieldElement::from_limbs([
+            210_821_654_958_261,
+            1_590_003_873_247_101,
+            641_571_940_451_751,
+            165_380_960_319_742,
+            1_417_356_887_857_858,
+        ]);
+        const BASEPOINT_Y: FieldElement = FieldElement::from_limbs([
+            965_057_063_007_956,
+            965_057_063_007_963,
+            965_057_063_007_963,
+            965_057_063_007_963,
+            965_057_063_007_963,
+        ]);
+    }
+
+    fn correct_params<C: CurveParams>() {
+        // d = -(A - 2) / (A + 2), and the root squares to -(A + 2) / B
+        let two = FieldElement::from(2);
+        assert_eq!(C::D * (C::A + two), -(C::A - two));
+        assert_eq!(C::ROOT_MINUS_A_MINUS_2.square() * C::B, -(C::A + two));
+
+        // the base point is on both curves
+        let (x2, y2) = (C::BASEPOINT_X.square(), C::BASEPOINT_Y.square());
+        assert_eq!(y2 - x2, FieldElement::ONE + C::D * x2 * y2);
+        let m_point = MPoint::<C>::try_from(&ProjEdPoint::<C>::generic_basepoint()).unwrap();
+        let (u, v) = (*m_point.x(), *m_point.y());
+        assert_eq!(
+            C::B * v.square(),
+            (u.square() + C::A * u + FieldElement::ONE) * u
+        );
+    }
+
+    #[test]
+    fn correct_curve25519_params() {
+        correct_params::<Curve25519>();
+    }
+
+    #[test]
+    fn correct_test_curve_params() {
+        correct_params::<TestCurve>();
+    }
+
+    // the generic code paths give the same results on another curve
+    #[test]
+    fn correct_test_curve_arithmetic() {
+        let b = ProjEdPoint::<TestCurve>::generic_basepoint();
+        let mut encoded = b;
+        assert_eq!(
+            ProjEdPoint::<TestCurve>::generic_decode(encoded.encode()).unwrap(),
+            b
+        );
+        assert_eq!(ProjEdPoint::from(&MPoint::try_from(&b).unwrap()), b);
+        assert_eq!(ProjEdPoint::from(&ProjMPoint::from(&b)), b);
+        assert_eq!(b.double(), b + &b);
+
+        let mut expected = ProjEdPoint::generic_identity();
+        for n in 1..16u8 {
+            expected += &b;
+            let mut scalar = [0u8; 32];
+            scalar[0] = n;
+            let x_b = MPoint::try_from(&b).unwrap();
+            let (x_n, _) = ladder::<TestCurve>(&scalar, x_b.x());
+            assert_eq!(x_n, *MPoint::try_from(&expected).unwrap().x());
+        }
+    }
+}
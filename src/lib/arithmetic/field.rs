@@ -0,0 +1,388 @@
+use super::super::Key;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+const LOW_51_BIT_MASK: u64 = (1 << 51) - 1;
+
+/// element of GF(2^255 - 19), stored as 5 limbs of 51 bits (little-endian)
+///
+/// limbs are kept below 2^52 between operations, so every value has several
+/// representations; use `to_bytes` to get the canonical one
+#[derive(Clone, Copy, Debug)]
+pub struct FieldElement([u64; 5]);
+
+impl FieldElement {
+    pub const ZERO: Self = Self([0, 0, 0, 0, 0]);
+    pub const ONE: Self = Self([1, 0, 0, 0, 0]);
+
+    // sqrt(-1) = 2^((p - 1) / 4)
+    pub const SQRT_M1: Self = Self([
+        1_718_705_420_411_056,
+        234_908_883_556_509,
+        2_233_514_472_574_048,
+        2_117_202_627_021_982,
+        765_476_049_583_133,
+    ]);
+
+    /// build an element from its 51-bit limbs (little-endian), each limb must be below 2^52
+    pub const fn from_limbs(limbs: [u64; 5]) -> Self {
+        Self(limbs)
+    }
+
+    /// carry the excess of each limb into the next one, folding the top carry back with 19
+    const fn reduce(mut limbs: [u64; 5]) -> Self {
+        let c0 = limbs[0] >> 51;
+        let c1 = limbs[1] >> 51;
+        let c2 = limbs[2] >> 51;
+        let c3 = limbs[3] >> 51;
+        let c4 = limbs[4] >> 51;
+
+        limbs[0] &= LOW_51_BIT_MASK;
+        limbs[1] &= LOW_51_BIT_MASK;
+        limbs[2] &= LOW_51_BIT_MASK;
+        limbs[3] &= LOW_51_BIT_MASK;
+        limbs[4] &= LOW_51_BIT_MASK;
+
+        limbs[0] += c4 * 19;
+        limbs[1] += c0;
+        limbs[2] += c1;
+        limbs[3] += c2;
+        limbs[4] += c3;
+
+        Self(limbs)
+    }
+
+    /// decode 32 little-endian bytes, ignoring the most significant bit
+    ///
+    /// non-canonical encodings (values in [p, 2^255)) are accepted and reduced
+    pub fn from_bytes(bytes: &Key) -> Self {
+        let mut limbs = [0u64; 5];
+        let mut acc: u128 = 0;
+        let mut bits = 0;
+        let mut i = 0;
+        for &byte in bytes {
+            acc |= u128::from(byte) << bits;
+            bits += 8;
+            if bits >= 51 && i < 5 {
+                limbs[i] = (acc as u64) & LOW_51_BIT_MASK;
+                acc >>= 51;
+                bits -= 51;
+                i += 1;
+            }
+        }
+        Self(limbs)
+    }
+
+    /// canonical little-endian encoding, the most significant bit is always 0
+    pub fn to_bytes(self) -> Key {
+        let mut limbs = Self::reduce(self.0).0;
+
+        // q = 1 iff the value is >= p, computed by checking whether value + 19 overflows 2^255
+        let mut q = (limbs[0] + 19) >> 51;
+        q = (limbs[1] + q) >> 51;
+        q = (limbs[2] + q) >> 51;
+        q = (limbs[3] + q) >> 51;
+        q = (limbs[4] + q) >> 51;
+
+        // subtract p by adding 19 and dropping the carry out of 2^255
+        limbs[0] += 19 * q;
+        limbs[1] += limbs[0] >> 51;
+        limbs[0] &= LOW_51_BIT_MASK;
+        limbs[2] += limbs[1] >> 51;
+        limbs[1] &= LOW_51_BIT_MASK;
+        limbs[3] += limbs[2] >> 51;
+        limbs[2] &= LOW_51_BIT_MASK;
+        limbs[4] += limbs[3] >> 51;
+        limbs[3] &= LOW_51_BIT_MASK;
+        limbs[4] &= LOW_51_BIT_MASK;
+
+        let mut bytes = [0u8; 32];
+        let mut acc: u128 = 0;
+        let mut bits = 0;
+        let mut i = 0;
+        for limb in limbs {
+            acc |= u128::from(limb) << bits;
+            bits += 51;
+            while bits >= 8 {
+                bytes[i] = acc as u8;
+                acc >>= 8;
+                bits -= 8;
+                i += 1;
+            }
+        }
+        bytes[i] = acc as u8;
+        bytes
+    }
+
+    /// the "sign" of RFC 8032, i.e. the least significant bit of the canonical encoding
+    pub fn is_negative(&self) -> Choice {
+        Choice::from(self.to_bytes()[0] & 1)
+    }
+
+    pub fn is_zero(&self) -> Choice {
+        self.to_bytes().ct_eq(&[0u8; 32])
+    }
+
+    pub fn square(&self) -> Self {
+        *self * *self
+    }
+
+    /// compute self^(2^k) by k successive squarings
+    pub fn pow2k(&self, k: u32) -> Self {
+        let mut result = *self;
+        for _ in 0..k {
+            result = result.square();
+        }
+        result
+    }
+
+    // returns (self^(2^250 - 1), self^11), shared by invert and pow_p58
+    fn pow22501(&self) -> (Self, Self) {
+        let t0 = self.square(); // 2
+        let t1 = t0.pow2k(2); // 8
+        let t2 = *self * t1; // 9
+        let t3 = t0 * t2; // 11
+        let t4 = t3.square(); // 22
+        let t5 = t2 * t4; // 2^5 - 1
+        let t6 = t5.pow2k(5) * t5; // 2^10 - 1
+        let t7 = t6.pow2k(10) * t6; // 2^20 - 1
+        let t8 = t7.pow2k(20) * t7; // 2^40 - 1
+        let t9 = t8.pow2k(10) * t6; // 2^50 - 1
+        let t10 = t9.pow2k(50) * t9; // 2^100 - 1
+        let t11 = t10.pow2k(100) * t10; // 2^200 - 1
+        let t12 = t11.pow2k(50) * t9; // 2^250 - 1
+        (t12, t3)
+    }
+
+    /// multiplicative inverse using Fermat's little theorem (self^(p - 2)), maps 0 to 0
+    pub fn invert(&self) -> Self {
+        let (t12, t3) = self.pow22501();
+        t12.pow2k(5) * t3
+    }
+
+    /// self^((p - 5) / 8) = self^(2^252 - 3)
+    fn pow_p58(&self) -> Self {
+        let (t12, _) = self.pow22501();
+        t12.pow2k(2) * *self
+    }
+
+    /// compute sqrt(u / v) as in RFC 8032 (section 5.1.3)
+    ///
+    /// returns (1, r) with r non-negative if u / v is a square (or v = u = 0),
+    /// and (0, r) otherwise, where r = sqrt(i * u / v) if it exists, without branching on u or v
+    pub fn sqrt_ratio_i(u: &Self, v: &Self) -> (Choice, Self) {
+        let v3 = v.square() * *v;
+        let v7 = v3.square() * *v;
+        let mut r = (*u * v3) * (*u * v7).pow_p58();
+        let check = *v * r.square();
+
+        let correct_sign = check.ct_eq(u);
+        let flipped_sign = check.ct_eq(&-*u);
+        let flipped_sign_i = check.ct_eq(&(-*u * Self::SQRT_M1));
+
+        let r_prime = r * Self::SQRT_M1;
+        r.conditional_assign(&r_prime, flipped_sign | flipped_sign_i);
+        let r_neg = -r;
+        r.conditional_assign(&r_neg, r.is_negative());
+
+        (correct_sign | flipped_sign, r)
+    }
+}
+
+impl From<u64> for FieldElement {
+    fn from(n: u64) -> Self {
+        Self::reduce([n, 0, 0, 0, 0])
+    }
+}
+
+impl ConditionallySelectable for FieldElement {
+    /// return b if choice is 1 and a otherwise, without branching on choice
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        let mask = 0u64.wrapping_sub(u64::from(choice.unwrap_u8()));
+        let mut limbs = a.0;
+        for (limb, other) in limbs.iter_mut().zip(b.0) {
+            *limb ^= mask & (*limb ^ other);
+        }
+        Self(limbs)
+    }
+}
+
+impl ConstantTimeEq for FieldElement {
+    /// compare the canonical encodings
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.to_bytes().ct_eq(&other.to_bytes())
+    }
+}
+
+impl PartialEq for FieldElement {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for FieldElement {}
+
+impl std::ops::Add for FieldElement {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let (a, b) = (self.0, rhs.0);
+        Self::reduce([
+            a[0] + b[0],
+            a[1] + b[1],
+            a[2] + b[2],
+            a[3] + b[3],
+            a[4] + b[4],
+        ])
+    }
+}
+
+impl std::ops::Sub for FieldElement {
+    type Output = Self;
+
+    // add 16p before subtracting so that no limb underflows
+    fn sub(self, rhs: Self) -> Self {
+        let (a, b) = (self.0, rhs.0);
+        Self::reduce([
+            (a[0] + 36_028_797_018_963_664) - b[0],
+            (a[1] + 36_028_797_018_963_952) - b[1],
+            (a[2] + 36_028_797_018_963_952) - b[2],
+            (a[3] + 36_028_797_018_963_952) - b[3],
+            (a[4] + 36_028_797_018_963_952) - b[4],
+        ])
+    }
+}
+
+impl std::ops::Neg for FieldElement {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::ZERO - self
+    }
+}
+
+impl std::ops::Mul for FieldElement {
+    type Output = Self;
+
+    // schoolbook multiplication, using 2^255 = 19 to fold the upper half of the product
+    fn mul(self, rhs: Self) -> Self {
+        #[inline(always)]
+        fn m(x: u64, y: u64) -> u128 {
+            u128::from(x) * u128::from(y)
+        }
+
+        let (a, b) = (self.0, rhs.0);
+
+        let b1_19 = b[1] * 19;
+        let b2_19 = b[2] * 19;
+        let b3_19 = b[3] * 19;
+        let b4_19 = b[4] * 19;
+
+        let c0 = m(a[0], b[0]) + m(a[4], b1_19) + m(a[3], b2_19) + m(a[2], b3_19) + m(a[1], b4_19);
+        let mut c1 =
+            m(a[1], b[0]) + m(a[0], b[1]) + m(a[4], b2_19) + m(a[3], b3_19) + m(a[2], b4_19);
+        let mut c2 =
+            m(a[2], b[0]) + m(a[1], b[1]) + m(a[0], b[2]) + m(a[4], b3_19) + m(a[3], b4_19);
+        let mut c3 = m(a[3], b[0]) + m(a[2], b[1]) + m(a[1], b[2]) + m(a[0], b[3]) + m(a[4], b4_19);
+        let mut c4 = m(a[4], b[0]) + m(a[3], b[1]) + m(a[2], b[2]) + m(a[1], b[3]) + m(a[0], b[4]);
+
+        c1 += c0 >> 51;
+        c2 += c1 >> 51;
+        c3 += c2 >> 51;
+        c4 += c3 >> 51;
+        let carry = (c4 >> 51) as u64;
+
+        let mut limbs = [
+            (c0 as u64) & LOW_51_BIT_MASK,
+            (c1 as u64) & LOW_51_BIT_MASK,
+            (c2 as u64) & LOW_51_BIT_MASK,
+            (c3 as u64) & LOW_51_BIT_MASK,
+            (c4 as u64) & LOW_51_BIT_MASK,
+        ];
+        limbs[0] += carry * 19;
+        limbs[1] += limbs[0] >> 51;
+        limbs[0] &= LOW_51_BIT_MASK;
+
+        Self(limbs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // p - 1, little-endian
+    const P_MINUS_ONE: Key = [
+        0xec, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0x7f,
+    ];
+
+    #[test]
+    fn correct_bytes_round_trip() {
+        assert_eq!(
+            FieldElement::from_bytes(&P_MINUS_ONE).to_bytes(),
+            P_MINUS_ONE
+        );
+    }
+
+    #[test]
+    fn correct_non_canonical_reduction() {
+        // p + 1 decodes to 1
+        let mut bytes = P_MINUS_ONE;
+        bytes[0] = 0xee;
+        assert_eq!(FieldElement::from_bytes(&bytes), FieldElement::ONE);
+    }
+
+    #[test]
+    fn correct_wrap_around() {
+        let minus_one = FieldElement::from_bytes(&P_MINUS_ONE);
+        assert_eq!(minus_one + FieldElement::ONE, FieldElement::ZERO);
+        assert_eq!(FieldElement::ZERO - FieldElement::ONE, minus_one);
+        assert_eq!(minus_one * minus_one, FieldElement::ONE);
+    }
+
+    #[test]
+    fn correct_sqrt_m1() {
+        assert_eq!(FieldElement::SQRT_M1.square(), -FieldElement::ONE);
+    }
+
+    #[test]
+    fn correct_invert() {
+        let x = FieldElement::from(486_662);
+        assert_eq!(x * x.invert(), FieldElement::ONE);
+        assert_eq!(FieldElement::ZERO.invert(), FieldElement::ZERO);
+    }
+
+    #[test]
+    fn correct_conditional_swap() {
+        let (mut a, mut b) = (FieldElement::ONE, FieldElement::from(2));
+        FieldElement::conditional_swap(&mut a, &mut b, Choice::from(0));
+        assert_eq!((a, b), (FieldElement::ONE, FieldElement::from(2)));
+        FieldElement::conditional_swap(&mut a, &mut b, Choice::from(1));
+        assert_eq!((a, b), (FieldElement::from(2), FieldElement::ONE));
+    }
+
+    #[test]
+    fn correct_ct_eq() {
+        // p + 1 and 1 have different limbs but are equal
+        let mut bytes = P_MINUS_ONE;
+        bytes[0] = 0xee;
+        let p_plus_one = FieldElement::from_bytes(&bytes);
+        assert!(bool::from(p_plus_one.ct_eq(&FieldElement::ONE)));
+        assert!(!bool::from(p_plus_one.ct_eq(&FieldElement::ZERO)));
+    }
+
+    #[test]
+    fn correct_sqrt_ratio_i() {
+        let four = FieldElement::from(4);
+        let (was_square, r) = FieldElement::sqrt_ratio_i(&four, &FieldElement::ONE);
+        assert!(bool::from(was_square));
+        assert_eq!(r, FieldElement::from(2));
+
+        // 2 is not a square modulo p
+        let (was_square, r) =
+            FieldElement::sqrt_ratio_i(&FieldElement::from(2), &FieldElement::ONE);
+        assert!(!bool::from(was_square));
+        assert_eq!(r.square(), FieldElement::from(2) * FieldElement::SQRT_M1);
+    }
+}
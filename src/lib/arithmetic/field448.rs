@@ -0,0 +1,317 @@
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+const LOW_56_BIT_MASK: u64 = (1 << 56) - 1;
+
+/// element of GF(2^448 - 2^224 - 1), stored as 8 limbs of 56 bits (little-endian)
+///
+/// limbs are kept below 2^57 between operations, so every value has several
+/// representations; use `to_bytes` to get the canonical one
+#[derive(Clone, Copy, Debug)]
+pub struct FieldElement448([u64; 8]);
+
+impl FieldElement448 {
+    pub const ZERO: Self = Self([0, 0, 0, 0, 0, 0, 0, 0]);
+    pub const ONE: Self = Self([1, 0, 0, 0, 0, 0, 0, 0]);
+
+    pub(crate) const fn from_limbs(limbs: [u64; 8]) -> Self {
+        Self(limbs)
+    }
+
+    /// carry the excess of each limb into the next one, folding the top carry back with
+    /// 2^448 = 2^224 + 1
+    const fn reduce(limbs: [u64; 8]) -> Self {
+        let mut out = [0u64; 8];
+        let mut i = 0;
+        while i < 8 {
+            out[i] = limbs[i] & LOW_56_BIT_MASK;
+            if i > 0 {
+                out[i] += limbs[i - 1] >> 56;
+            }
+            i += 1;
+        }
+        let top = limbs[7] >> 56;
+        out[0] += top;
+        out[4] += top;
+        Self(out)
+    }
+
+    /// decode 56 little-endian bytes
+    ///
+    /// non-canonical encodings (values in [p, 2^448)) are accepted and reduced
+    pub fn from_bytes(bytes: &[u8; 56]) -> Self {
+        let mut limbs = [0u64; 8];
+        for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks(7)) {
+            let mut word = [0u8; 8];
+            word[..7].copy_from_slice(chunk);
+            *limb = u64::from_le_bytes(word);
+        }
+        Self(limbs)
+    }
+
+    /// canonical little-endian encoding
+    pub fn to_bytes(self) -> [u8; 56] {
+        let mut limbs = Self::reduce(self.0).0;
+
+        // carry sequentially until every limb is below 2^56, i.e. the value is below 2^448
+        for _ in 0..3 {
+            let mut carry = 0;
+            for limb in limbs.iter_mut() {
+                *limb += carry;
+                carry = *limb >> 56;
+                *limb &= LOW_56_BIT_MASK;
+            }
+            limbs[0] += carry;
+            limbs[4] += carry;
+        }
+
+        // q = 1 iff the value is >= p, computed by checking whether value + 2^224 + 1
+        // overflows 2^448
+        let mut q = (limbs[0] + 1) >> 56;
+        for (i, &limb) in limbs.iter().enumerate().skip(1) {
+            q = (limb + q + u64::from(i == 4)) >> 56;
+        }
+
+        // subtract p by adding 2^224 + 1 and dropping the carry out of 2^448
+        limbs[0] += q;
+        limbs[4] += q;
+        let mut carry = 0;
+        for limb in limbs.iter_mut() {
+            *limb += carry;
+            carry = *limb >> 56;
+            *limb &= LOW_56_BIT_MASK;
+        }
+
+        let mut bytes = [0u8; 56];
+        for (chunk, limb) in bytes.chunks_mut(7).zip(limbs) {
+            chunk.copy_from_slice(&limb.to_le_bytes()[..7]);
+        }
+        bytes
+    }
+
+    /// the "sign" of RFC 8032, i.e. the least significant bit of the canonical encoding
+    pub fn is_negative(&self) -> Choice {
+        Choice::from(self.to_bytes()[0] & 1)
+    }
+
+    pub fn is_zero(&self) -> Choice {
+        self.to_bytes().ct_eq(&[0u8; 56])
+    }
+
+    pub fn square(&self) -> Self {
+        *self * *self
+    }
+
+    /// compute self^(2^k) by k successive squarings
+    pub fn pow2k(&self, k: u32) -> Self {
+        let mut result = *self;
+        for _ in 0..k {
+            result = result.square();
+        }
+        result
+    }
+
+    /// self^((p - 3) / 4) = self^(2^446 - 2^222 - 1) = self^((2^223 - 1) 2^223 + 2^222 - 1)
+    fn pow_p34(&self) -> Self {
+        let t1 = *self;
+        let t2 = t1.square() * t1; // 2^2 - 1
+        let t3 = t2.square() * t1; // 2^3 - 1
+        let t6 = t3.pow2k(3) * t3; // 2^6 - 1
+        let t12 = t6.pow2k(6) * t6; // 2^12 - 1
+        let t24 = t12.pow2k(12) * t12; // 2^24 - 1
+        let t48 = t24.pow2k(24) * t24; // 2^48 - 1
+        let t96 = t48.pow2k(48) * t48; // 2^96 - 1
+        let t192 = t96.pow2k(96) * t96; // 2^192 - 1
+        let t216 = t192.pow2k(24) * t24; // 2^216 - 1
+        let t222 = t216.pow2k(6) * t6; // 2^222 - 1
+        let t223 = t222.square() * t1; // 2^223 - 1
+        t223.pow2k(223) * t222
+    }
+
+    /// multiplicative inverse using Fermat's little theorem (self^(p - 2)), maps 0 to 0
+    pub fn invert(&self) -> Self {
+        // p - 2 = 4 (p - 3) / 4 + 1
+        self.pow_p34().pow2k(2) * *self
+    }
+
+    /// compute sqrt(u / v) as in RFC 8032 (section 5.2.3)
+    ///
+    /// returns (1, r) if u / v is a square (or v = u = 0), and (0, r) otherwise, without
+    /// branching on u or v
+    pub fn sqrt_ratio(u: &Self, v: &Self) -> (Choice, Self) {
+        let u3v = u.square() * *u * *v;
+        let u5v3 = u3v * u.square() * v.square();
+        let r = u3v * u5v3.pow_p34();
+        let was_square = (*v * r.square()).ct_eq(u);
+        (was_square, r)
+    }
+}
+
+impl ConditionallySelectable for FieldElement448 {
+    /// return b if choice is 1 and a otherwise, without branching on choice
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        let mask = 0u64.wrapping_sub(u64::from(choice.unwrap_u8()));
+        let mut limbs = a.0;
+        for (limb, other) in limbs.iter_mut().zip(b.0) {
+            *limb ^= mask & (*limb ^ other);
+        }
+        Self(limbs)
+    }
+}
+
+impl ConstantTimeEq for FieldElement448 {
+    /// compare the canonical encodings
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.to_bytes().ct_eq(&other.to_bytes())
+    }
+}
+
+impl From<u64> for FieldElement448 {
+    fn from(n: u64) -> Self {
+        Self::reduce([n, 0, 0, 0, 0, 0, 0, 0])
+    }
+}
+
+impl PartialEq for FieldElement448 {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for FieldElement448 {}
+
+impl std::ops::Add for FieldElement448 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let mut limbs = self.0;
+        for (limb, other) in limbs.iter_mut().zip(rhs.0) {
+            *limb += other;
+        }
+        Self::reduce(limbs)
+    }
+}
+
+impl std::ops::Sub for FieldElement448 {
+    type Output = Self;
+
+    // add 4p before subtracting so that no limb underflows
+    fn sub(self, rhs: Self) -> Self {
+        let mut limbs = self.0;
+        for (i, (limb, other)) in limbs.iter_mut().zip(rhs.0).enumerate() {
+            let four_p = if i == 4 { (1 << 58) - 8 } else { (1 << 58) - 4 };
+            *limb = (*limb + four_p) - other;
+        }
+        Self::reduce(limbs)
+    }
+}
+
+impl std::ops::Neg for FieldElement448 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::ZERO - self
+    }
+}
+
+impl std::ops::Mul for FieldElement448 {
+    type Output = Self;
+
+    // schoolbook multiplication, using 2^448 = 2^224 + 1 to fold the upper half of the product
+    fn mul(self, rhs: Self) -> Self {
+        let (a, b) = (self.0, rhs.0);
+
+        let mut c = [0u128; 15];
+        for i in 0..8 {
+            for j in 0..8 {
+                c[i + j] += u128::from(a[i]) * u128::from(b[j]);
+            }
+        }
+        // c[i] 2^(56 i) = c[i] 2^(56 (i - 8)) (2^224 + 1), from the top so that the limbs
+        // folded into 8..12 are folded again
+        for i in (8..15).rev() {
+            c[i - 4] += c[i];
+            c[i - 8] += c[i];
+        }
+
+        let mut limbs = [0u128; 8];
+        limbs.copy_from_slice(&c[..8]);
+        for _ in 0..2 {
+            let mut carry = 0;
+            for limb in limbs.iter_mut() {
+                *limb += carry;
+                carry = *limb >> 56;
+                *limb &= u128::from(LOW_56_BIT_MASK);
+            }
+            limbs[0] += carry;
+            limbs[4] += carry;
+        }
+
+        Self(limbs.map(|limb| limb as u64))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // p - 1, little-endian
+    fn p_minus_one() -> [u8; 56] {
+        let mut bytes = [0xff; 56];
+        bytes[0] = 0xfe;
+        bytes[28] = 0xfe;
+        bytes
+    }
+
+    #[test]
+    fn correct_bytes_round_trip() {
+        let bytes = p_minus_one();
+        assert_eq!(FieldElement448::from_bytes(&bytes).to_bytes(), bytes);
+    }
+
+    #[test]
+    fn correct_non_canonical_reduction() {
+        // p + 1 and 2^448 - 1 = p + 2^224 decode to 1 and 2^224
+        let mut bytes = [0xff; 56];
+        bytes[..28].fill(0);
+        let mut expected = [0u8; 56];
+        expected[0] = 1;
+        assert_eq!(FieldElement448::from_bytes(&bytes).to_bytes(), expected);
+
+        let mut expected = [0u8; 56];
+        expected[28] = 1;
+        assert_eq!(
+            FieldElement448::from_bytes(&[0xff; 56]).to_bytes(),
+            expected
+        );
+    }
+
+    #[test]
+    fn correct_wrap_around() {
+        let minus_one = FieldElement448::from_bytes(&p_minus_one());
+        assert_eq!(minus_one + FieldElement448::ONE, FieldElement448::ZERO);
+        assert_eq!(FieldElement448::ZERO - FieldElement448::ONE, minus_one);
+        assert_eq!(minus_one * minus_one, FieldElement448::ONE);
+    }
+
+    #[test]
+    fn correct_invert() {
+        let x = FieldElement448::from(39_081);
+        assert_eq!(x * x.invert(), FieldElement448::ONE);
+        let minus_one = FieldElement448::from_bytes(&p_minus_one());
+        assert_eq!(minus_one.invert(), minus_one);
+        assert_eq!(FieldElement448::ZERO.invert(), FieldElement448::ZERO);
+    }
+
+    #[test]
+    fn correct_sqrt_ratio() {
+        let (was_square, r) =
+            FieldElement448::sqrt_ratio(&FieldElement448::from(36), &FieldElement448::from(4));
+        assert!(bool::from(was_square));
+        assert_eq!(r.square(), FieldElement448::from(9));
+
+        // p = 3 mod 4, so -1 is not a square
+        let minus_one = -FieldElement448::ONE;
+        let (was_square, _) = FieldElement448::sqrt_ratio(&minus_one, &FieldElement448::ONE);
+        assert!(!bool::from(was_square));
+    }
+}
@@ -0,0 +1,340 @@
+use super::field::FieldElement;
+use super::montgomery::MPoint;
+use super::proj_edwards::{ProjEdPoint, EIGHT_TORSION};
+use super::proj_montgomery::ProjMPoint;
+use sha2::{Digest, Sha512};
+
+// SHA-512 output and block sizes
+const B_IN_BYTES: usize = 64;
+const S_IN_BYTES: usize = 128;
+
+// ceil((ceil(log2(p)) + k) / 8) with k = 128
+const L: usize = 48;
+
+/// expand_message_xmd from RFC 9380 (section 5.3.1) with SHA-512
+///
+/// panics if len_in_bytes > 255 * 64, a dst longer than 255 bytes is hashed first (section 5.3.3)
+pub fn expand_message_xmd(msg: &[u8], dst: &[u8], len_in_bytes: usize) -> Vec<u8> {
+    let ell = len_in_bytes.div_ceil(B_IN_BYTES);
+    assert!(ell <= 255);
+
+    let dst = if dst.len() > 255 {
+        Sha512::new()
+            .chain_update(b"H2C-OVERSIZE-DST-")
+            .chain_update(dst)
+            .finalize()
+            .to_vec()
+    } else {
+        dst.to_vec()
+    };
+    // DST_prime = DST || I2OSP(len(DST), 1)
+    let dst_prime = [dst.as_slice(), &[dst.len() as u8]].concat();
+
+    let b_0 = Sha512::new()
+        .chain_update([0u8; S_IN_BYTES])
+        .chain_update(msg)
+        .chain_update((len_in_bytes as u16).to_be_bytes())
+        .chain_update([0u8])
+        .chain_update(&dst_prime)
+        .finalize();
+
+    let mut uniform_bytes = Vec::with_capacity(ell * B_IN_BYTES);
+    let mut b_i = Sha512::new()
+        .chain_update(b_0)
+        .chain_update([1u8])
+        .chain_update(&dst_prime)
+        .finalize();
+    uniform_bytes.extend_from_slice(&b_i);
+    for i in 2..=ell {
+        // b_i = H(strxor(b_0, b_(i - 1)) || I2OSP(i, 1) || DST_prime)
+        let xored = b_0
+            .iter()
+            .zip(&b_i)
+            .map(|(x, y)| x ^ y)
+            .collect::<Vec<u8>>();
+        b_i = Sha512::new()
+            .chain_update(xored)
+            .chain_update([i as u8])
+            .chain_update(&dst_prime)
+            .finalize();
+        uniform_bytes.extend_from_slice(&b_i);
+    }
+    uniform_bytes.truncate(len_in_bytes);
+    uniform_bytes
+}
+
+// OS2IP(bytes) mod p, for L = 48 big-endian bytes
+fn field_from_be_bytes(bytes: &[u8]) -> FieldElement {
+    // bytes = c_2 * 2^256 + c_1 * 2^128 + c_0, with c_i < 2^128
+    let chunk = |i: usize| {
+        let mut le_bytes = [0u8; 32];
+        le_bytes[..16].copy_from_slice(&bytes[L - 16 * (i + 1)..L - 16 * i]);
+        le_bytes[..16].reverse();
+        FieldElement::from_bytes(&le_bytes)
+    };
+    let mut two_128 = [0u8; 32];
+    two_128[16] = 1;
+    let two_128 = FieldElement::from_bytes(&two_128);
+
+    (chunk(2) * two_128 + chunk(1)) * two_128 + chunk(0)
+}
+
+/// hash_to_field from RFC 9380 (section 5.2) for GF(2^255 - 19), with expand_message_xmd
+fn hash_to_field<const COUNT: usize>(msg: &[u8], dst: &[u8]) -> [FieldElement; COUNT] {
+    let uniform_bytes = expand_message_xmd(msg, dst, COUNT * L);
+    let mut u = [FieldElement::ZERO; COUNT];
+    for (u_i, bytes) in u.iter_mut().zip(uniform_bytes.chunks(L)) {
+        *u_i = field_from_be_bytes(bytes);
+    }
+    u
+}
+
+// rational map from RFC 9380 (appendix D), which sends the exceptional point (0, 0) to the
+// identity instead of (0, -1)
+fn edwards_from_montgomery(point: &MPoint) -> ProjEdPoint {
+    if bool::from(point.y().is_zero()) {
+        ProjEdPoint::identity()
+    } else {
+        ProjEdPoint::from(point)
+    }
+}
+
+/// suite edwards25519_XMD:SHA-512_ELL2_RO_, the output is uniformly distributed in the subgroup
+/// of order L
+pub fn hash_to_edwards25519(msg: &[u8], dst: &[u8]) -> ProjEdPoint {
+    let [u0, u1] = hash_to_field::<2>(msg, dst);
+    let q0 = edwards_from_montgomery(&MPoint::elligator2(&u0));
+    let q1 = edwards_from_montgomery(&MPoint::elligator2(&u1));
+    (q0 + &q1).mul_by_cofactor()
+}
+
+/// suite edwards25519_XMD:SHA-512_ELL2_NU_, cheaper than `hash_to_edwards25519` but the output
+/// is not uniformly distributed
+pub fn encode_to_edwards25519(msg: &[u8], dst: &[u8]) -> ProjEdPoint {
+    let [u] = hash_to_field::<1>(msg, dst);
+    edwards_from_montgomery(&MPoint::elligator2(&u)).mul_by_cofactor()
+}
+
+/// suite curve25519_XMD:SHA-512_ELL2_RO_, the output is the point at infinity with negligible
+/// probability
+pub fn hash_to_curve25519(msg: &[u8], dst: &[u8]) -> ProjMPoint {
+    // the sum is computed on edwards25519, through the isomorphism which sends (0, 0) to (0, -1)
+    let to_edwards = |point: MPoint| {
+        if bool::from(point.y().is_zero()) {
+            EIGHT_TORSION[4]
+        } else {
+            ProjEdPoint::from(&point)
+        }
+    };
+    let [u0, u1] = hash_to_field::<2>(msg, dst);
+    let q0 = to_edwards(MPoint::elligator2(&u0));
+    let q1 = to_edwards(MPoint::elligator2(&u1));
+    ProjMPoint::from(&(q0 + &q1).mul_by_cofactor())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lib::arithmetic::curve::{Curve25519, CurveParams};
+
+    fn bytes_from_str(s: &str) -> Vec<u8> {
+        s.chars()
+            .collect::<Vec<char>>()
+            .chunks(2)
+            .map(|chunk| chunk.iter().collect::<String>())
+            .map(|byte| u8::from_str_radix(&byte, 16).unwrap())
+            .collect()
+    }
+
+    // big-endian hex, as in the RFC
+    fn field_from_str(s: &str) -> FieldElement {
+        let mut bytes: [u8; 32] = bytes_from_str(s).try_into().unwrap();
+        bytes.reverse();
+        FieldElement::from_bytes(&bytes)
+    }
+
+    fn messages() -> [Vec<u8>; 5] {
+        [
+            b"".to_vec(),
+            b"abc".to_vec(),
+            b"abcdef0123456789".to_vec(),
+            [b"q128_".as_slice(), &[b'q'; 128]].concat(),
+            [b"a512_".as_slice(), &[b'a'; 512]].concat(),
+        ]
+    }
+
+    // RFC 9380, appendix K.3
+    #[test]
+    fn correct_expand_message_xmd() {
+        let dst = b"QUUX-V01-CS02-with-expander-SHA512-256";
+        let uniform_bytes_32 = [
+            "6b9a7312411d92f921c6f68ca0b6380730a1a4d982c507211a90964c394179ba",
+            "0da749f12fbe5483eb066a5f595055679b976e93abe9be6f0f6318bce7aca8dc",
+            "087e45a86e2939ee8b91100af1583c4938e0f5fc6c9db4b107b83346bc967f58",
+            "7336234ee9983902440f6bc35b348352013becd88938d2afec44311caf8356b3",
+            "57b5f7e766d5be68a6bfe1768e3c2b7f1228b3e4b3134956dd73a59b954c66f4",
+        ];
+        let uniform_bytes_128 = [
+            "41b037d1734a5f8df225dd8c7de38f851efdb45c372887be655212d07251b921\
+             b052b62eaed99b46f72f2ef4cc96bfaf254ebbbec091e1a3b9e4fb5e5b619d2e\
+             0c5414800a1d882b62bb5cd1778f098b8eb6cb399d5d9d18f5d5842cf5d13d7e\
+             b00a7cff859b605da678b318bd0e65ebff70bec88c753b159a805d2c89c55961",
+            "7f1dddd13c08b543f2e2037b14cefb255b44c83cc397c1786d975653e36a6b11\
+             bdd7732d8b38adb4a0edc26a0cef4bb45217135456e58fbca1703cd6032cb134\
+             7ee720b87972d63fbf232587043ed2901bce7f22610c0419751c065922b48843\
+             1851041310ad659e4b23520e1772ab29dcdeb2002222a363f0c2b1c972b3efe1",
+            "3f721f208e6199fe903545abc26c837ce59ac6fa45733f1baaf0222f8b7acb04\
+             24814fcb5eecf6c1d38f06e9d0a6ccfbf85ae612ab8735dfdf9ce84c372a77c8\
+             f9e1c1e952c3a61b7567dd0693016af51d2745822663d0c2367e3f4f0bed827f\
+             eecc2aaf98c949b5ed0d35c3f1023d64ad1407924288d366ea159f46287e61ac",
+            "b799b045a58c8d2b4334cf54b78260b45eec544f9f2fb5bd12fb603eaee70db7\
+             317bf807c406e26373922b7b8920fa29142703dd52bdf280084fb7ef69da78af\
+             df80b3586395b433dc66cde048a258e476a561e9deba7060af40adf30c64249c\
+             a7ddea79806ee5beb9a1422949471d267b21bc88e688e4014087a0b592b695ed",
+            "05b0bfef265dcee87654372777b7c44177e2ae4c13a27f103340d9cd11c86cb2\
+             426ffcad5bd964080c2aee97f03be1ca18e30a1f14e27bc11ebbd650f305269c\
+             c9fb1db08bf90bfc79b42a952b46daf810359e7bc36452684784a64952c343c5\
+             2e5124cd1f71d474d5197fefc571a92929c9084ffe1112cf5eea5192ebff330b",
+        ];
+        for (msg, expected) in messages().iter().zip(uniform_bytes_32) {
+            assert_eq!(expand_message_xmd(msg, dst, 32), bytes_from_str(expected));
+        }
+        for (msg, expected) in messages().iter().zip(uniform_bytes_128) {
+            assert_eq!(expand_message_xmd(msg, dst, 128), bytes_from_str(expected));
+        }
+    }
+
+    fn correct_edwards_suite(
+        hash: fn(&[u8], &[u8]) -> ProjEdPoint,
+        dst: &[u8],
+        expected: [(&str, &str); 5],
+    ) {
+        for (msg, (x, y)) in messages().iter().zip(expected) {
+            let point = hash(msg, dst);
+            assert_eq!(
+                point,
+                ProjEdPoint::new(field_from_str(x), field_from_str(y))
+            );
+            assert!(point.is_torsion_free());
+        }
+    }
+
+    // RFC 9380, appendix J.5.1
+    #[test]
+    fn correct_hash_to_edwards25519() {
+        correct_edwards_suite(
+            hash_to_edwards25519,
+            b"QUUX-V01-CS02-with-edwards25519_XMD:SHA-512_ELL2_RO_",
+            [
+                (
+                    "3c3da6925a3c3c268448dcabb47ccde5439559d9599646a8260e47b1e4822fc6",
+                    "09a6c8561a0b22bef63124c588ce4c62ea83a3c899763af26d795302e115dc21",
+                ),
+                (
+                    "608040b42285cc0d72cbb3985c6b04c935370c7361f4b7fbdb1ae7f8c1a8ecad",
+                    "1a8395b88338f22e435bbd301183e7f20a5f9de643f11882fb237f88268a5531",
+                ),
+                (
+                    "6d7fabf47a2dc03fe7d47f7dddd21082c5fb8f86743cd020f3fb147d57161472",
+                    "53060a3d140e7fbcda641ed3cf42c88a75411e648a1add71217f70ea8ec561a6",
+                ),
+                (
+                    "5fb0b92acedd16f3bcb0ef83f5c7b7a9466b5f1e0d8d217421878ea3686f8524",
+                    "2eca15e355fcfa39d2982f67ddb0eea138e2994f5956ed37b7f72eea5e89d2f7",
+                ),
+                (
+                    "0efcfde5898a839b00997fbe40d2ebe950bc81181afbd5cd6b9618aa336c1e8c",
+                    "6dc2fc04f266c5c27f236a80b14f92ccd051ef1ff027f26a07f8c0f327d8f995",
+                ),
+            ],
+        );
+    }
+
+    // RFC 9380, appendix J.5.2
+    #[test]
+    fn correct_encode_to_edwards25519() {
+        correct_edwards_suite(
+            encode_to_edwards25519,
+            b"QUUX-V01-CS02-with-edwards25519_XMD:SHA-512_ELL2_NU_",
+            [
+                (
+                    "1ff2b70ecf862799e11b7ae744e3489aa058ce805dd323a936375a84695e76da",
+                    "222e314d04a4d5725e9f2aff9fb2a6b69ef375a1214eb19021ceab2d687f0f9b",
+                ),
+                (
+                    "5f13cc69c891d86927eb37bd4afc6672360007c63f68a33ab423a3aa040fd2a8",
+                    "67732d50f9a26f73111dd1ed5dba225614e538599db58ba30aaea1f5c827fa42",
+                ),
+                (
+                    "1dd2fefce934ecfd7aae6ec998de088d7dd03316aa1847198aecf699ba6613f1",
+                    "2f8a6c24dd1adde73909cada6a4a137577b0f179d336685c4a955a0a8e1a86fb",
+                ),
+                (
+                    "35fbdc5143e8a97afd3096f2b843e07df72e15bfca2eaf6879bf97c5d3362f73",
+                    "2af6ff6ef5ebba128b0774f4296cb4c2279a074658b083b8dcca91f57a603450",
+                ),
+                (
+                    "6e5e1f37e99345887fc12111575fc1c3e36df4b289b8759d23af14d774b66bff",
+                    "2c90c3d39eb18ff291d33441b35f3262cdd307162cc97c31bfcc7a4245891a37",
+                ),
+            ],
+        );
+    }
+
+    // RFC 9380, appendix J.4.1
+    #[test]
+    fn correct_hash_to_curve25519() {
+        let dst = b"QUUX-V01-CS02-with-curve25519_XMD:SHA-512_ELL2_RO_";
+        let expected = [
+            (
+                "2de3780abb67e861289f5749d16d3e217ffa722192d16bbd9d1bfb9d112b98c0",
+                "3b5dc2a498941a1033d176567d457845637554a2fe7a3507d21abd1c1bd6e878",
+            ),
+            (
+                "2b4419f1f2d48f5872de692b0aca72cc7b0a60915dd70bde432e826b6abc526d",
+                "1b8235f255a268f0a6fa8763e97eb3d22d149343d495da1160eff9703f2d07dd",
+            ),
+            (
+                "68ca1ea5a6acf4e9956daa101709b1eee6c1bb0df1de3b90d4602382a104c036",
+                "2a375b656207123d10766e68b938b1812a4a6625ff83cb8d5e86f58a4be08353",
+            ),
+            (
+                "096e9c8bae6c06b554c1ee69383bb0e82267e064236b3a30608d4ed20b73ac5a",
+                "1eb5a62612cafb32b16c3329794645b5b948d9f8ffe501d4e26b073fef6de355",
+            ),
+            (
+                "1bc61845a138e912f047b5e70ba9606ba2a447a4dade024c8ef3dd42b7bbc5fe",
+                "623d05e47b70e25f7f1d51dda6d7c23c9a18ce015fe3548df596ea9e38c69bf1",
+            ),
+        ];
+        for (msg, (x, y)) in messages().iter().zip(expected) {
+            let point = MPoint::try_from(&hash_to_curve25519(msg, dst)).unwrap();
+            assert_eq!(*point.x(), field_from_str(x));
+            assert_eq!(*point.y(), field_from_str(y));
+        }
+    }
+
+    #[test]
+    fn correct_hash_to_field() {
+        let dst = b"QUUX-V01-CS02-with-curve25519_XMD:SHA-512_ELL2_RO_";
+        let [u0, u1] = hash_to_field::<2>(b"", dst);
+        assert_eq!(
+            u0,
+            field_from_str("005fe8a7b8fef0a16c105e6cadf5a6740b3365e18692a9c05bfbb4d97f645a6a")
+        );
+        assert_eq!(
+            u1,
+            field_from_str("1347edbec6a2b5d8c02e058819819bee177077c9d10a4ce165aab0fd0252261a")
+        );
+    }
+
+    #[test]
+    fn correct_elligator2_on_curve() {
+        let a = Curve25519::A;
+        for n in [0, 1, 2, 3, 1_000_000] {
+            let point = MPoint::elligator2(&FieldElement::from(n));
+            let (x, y) = (*point.x(), *point.y());
+            assert_eq!(y.square(), (x.square() + a * x + FieldElement::ONE) * x);
+        }
+    }
+}
@@ -1,51 +1,168 @@
-use super::{proj_edwards::ProjEdPoint, proj_montgomery::ProjMPoint};
-use rug::Integer;
+use super::super::{Error, Key};
+use super::curve::{Curve25519, CurveParams};
+use super::{field::FieldElement, proj_edwards::ProjEdPoint, proj_montgomery::ProjMPoint};
+use std::marker::PhantomData;
+use subtle::ConditionallySelectable;
 
-#[derive(Clone)]
-pub struct MPoint {
-    x: Integer,
-    y: Integer,
+/// affine point (x, y) of the Montgomery curve of C
+#[derive(Clone, Copy)]
+pub struct MPoint<C: CurveParams = Curve25519> {
+    x: FieldElement,
+    y: FieldElement,
+    curve: PhantomData<C>,
 }
 
-impl MPoint {
-    pub fn new(x: Integer, y: Integer) -> Self {
-        Self { x, y }
+impl<C: CurveParams> MPoint<C> {
+    pub const fn new(x: FieldElement, y: FieldElement) -> Self {
+        Self {
+            x,
+            y,
+            curve: PhantomData,
+        }
     }
 
-    pub const fn x(&self) -> &Integer {
+    pub const fn x(&self) -> &FieldElement {
         &self.x
     }
 
-    pub const fn y(&self) -> &Integer {
+    pub const fn y(&self) -> &FieldElement {
         &self.y
     }
 }
 
-impl TryFrom<&ProjMPoint> for MPoint {
-    type Error = Integer;
-    fn try_from(point: &ProjMPoint) -> Result<Self, Integer> {
-        let p = ProjEdPoint::p();
-        let x = (point.x().clone() * point.z().clone().invert(&p)?).modulo(&p);
-        let y = (point.y().clone() * point.z().clone().invert(&p)?).modulo(&p);
-        Ok(Self::new(x, y))
+// RFC 9380 only defines the map for curve25519
+impl MPoint {
+    /// Elligator 2 map from RFC 9380 (section 6.7.1) onto curve25519, with Z = 2
+    pub fn elligator2(u: &FieldElement) -> Self {
+        let a = Curve25519::A;
+        // y^2 = x^3 + a x^2 + x
+        let g = |x: FieldElement| (x.square() + a * x + FieldElement::ONE) * x;
+
+        // x1 = -a / (1 + 2 u^2), or -a if the denominator is 0
+        let x1 = -a * (FieldElement::ONE + FieldElement::from(2) * u.square()).invert();
+        let x1 = FieldElement::conditional_select(&x1, &-a, x1.is_zero());
+        let x2 = -x1 - a;
+
+        // exactly one of g(x1) and g(x2) is a square, y is non-negative at this point
+        let (gx1_is_square, y1) = FieldElement::sqrt_ratio_i(&g(x1), &FieldElement::ONE);
+        let (_, y2) = FieldElement::sqrt_ratio_i(&g(x2), &FieldElement::ONE);
+
+        // sgn0(y) = 1 for x1 and 0 for x2
+        let x = FieldElement::conditional_select(&x2, &x1, gx1_is_square);
+        let y = FieldElement::conditional_select(&y2, &-y1, gx1_is_square);
+        Self::new(x, y)
+    }
+
+    /// inverse of `elligator2`: the non-negative u such that elligator2(u) = self, if any
+    ///
+    /// about half of the points have one, runs in variable time
+    pub fn elligator2_inverse(&self) -> Option<FieldElement> {
+        let a = Curve25519::A;
+        let two = FieldElement::from(2);
+        let x_plus_a = self.x + a;
+        if bool::from(x_plus_a.is_zero()) {
+            return None;
+        }
+
+        // self comes from x1 = -a / (1 + 2 u^2) iff y is negative, otherwise from x2 = -x1 - a
+        let (was_square, u) = if bool::from(self.y.is_negative()) {
+            FieldElement::sqrt_ratio_i(&-x_plus_a, &(two * self.x))
+        } else {
+            FieldElement::sqrt_ratio_i(&-self.x, &(two * x_plus_a))
+        };
+
+        // the map may pick the other branch for this u, e.g. when x = 0
+        let image = Self::elligator2(&u);
+        (bool::from(was_square) && image.x == self.x && image.y == self.y).then_some(u)
+    }
+
+    /// 32-byte representative of self, indistinguishable from random bytes when self is a uniformly
+    /// random point of the whole curve (in any of the 8 cosets of the prime-order subgroup) with a
+    /// representative, except for the two top bits which are always 0
+    ///
+    /// callers should fill the two top bits with random bits
+    pub fn to_representative(&self) -> Option<Key> {
+        self.elligator2_inverse().map(|u| {
+            // u and -u have the same image, and one of them is below 2^254
+            let bytes = u.to_bytes();
+            if bytes[31] >> 6 == 0 {
+                bytes
+            } else {
+                (-u).to_bytes()
+            }
+        })
+    }
+
+    /// inverse of `to_representative`, ignoring the two top bits
+    pub fn from_representative(representative: &Key) -> Self {
+        let mut bytes = *representative;
+        bytes[31] &= 0b0011_1111;
+        Self::elligator2(&FieldElement::from_bytes(&bytes))
+    }
+}
+
+// fails with `SmallOrder` on the point at infinity
+impl<C: CurveParams> TryFrom<&ProjMPoint<C>> for MPoint<C> {
+    type Error = Error;
+    fn try_from(point: &ProjMPoint<C>) -> Result<Self, Error> {
+        if bool::from(point.z().is_zero()) {
+            return Err(Error::SmallOrder);
+        }
+        let z_inv = point.z().invert();
+        Ok(Self::new(*point.x() * z_inv, *point.y() * z_inv))
     }
 }
 
 // x = (z + y) / (z - y), y = ((z + y) * z) / ((z - y) * x)
-impl TryFrom<&ProjEdPoint> for MPoint {
-    type Error = Integer;
-    fn try_from(point: &ProjEdPoint) -> Result<Self, Integer> {
-        let p = ProjEdPoint::p();
-        let root = ProjEdPoint::root_minus_a_minus_2_mod_p();
-        let x = ((point.z().clone() + point.y()) * (point.z().clone() - point.y()).invert(&p)?)
-            .modulo(&p);
-        let y = ((((point.z().clone() + point.y())
-            * (point.z().clone() - point.y()).invert(&p)?)
-        .modulo(&p)
-            * (point.x().clone().invert(&p).unwrap() * point.z()).modulo(&p))
-        .modulo(&p)
-            * root)
-            .modulo(&p);
+// fails with `SmallOrder` on the identity and on (0, -1)
+impl<C: CurveParams> TryFrom<&ProjEdPoint<C>> for MPoint<C> {
+    type Error = Error;
+    fn try_from(point: &ProjEdPoint<C>) -> Result<Self, Error> {
+        let root = C::ROOT_MINUS_A_MINUS_2;
+        let z_minus_y = *point.z() - *point.y();
+        if bool::from(z_minus_y.is_zero() | point.x().is_zero()) {
+            return Err(Error::SmallOrder);
+        }
+        let x = (*point.z() + *point.y()) * z_minus_y.invert();
+        let y = x * (point.x().invert() * *point.z()) * root;
         Ok(Self::new(x, y))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lib::arithmetic::scalar::Scalar;
+
+    #[test]
+    fn correct_elligator2_inverse() {
+        for n in 0..64 {
+            let u = FieldElement::from(n);
+            let point = MPoint::elligator2(&u);
+            let inverse = point.elligator2_inverse().unwrap();
+            assert!(inverse == u || inverse == -u);
+            assert!(!bool::from(inverse.is_negative()));
+        }
+    }
+
+    #[test]
+    fn correct_representative() {
+        let mut representable = 0;
+        for n in 1..=64 {
+            let point = ProjEdPoint::mul_base(&Scalar::from(n));
+            let m_point = MPoint::try_from(&point).unwrap();
+            if let Some(mut representative) = m_point.to_representative() {
+                representable += 1;
+                assert_eq!(representative[31] >> 6, 0);
+                representative[31] |= 0b1100_0000;
+
+                let decoded = MPoint::from_representative(&representative);
+                assert_eq!(*decoded.x(), *m_point.x());
+                assert_eq!(*decoded.y(), *m_point.y());
+                assert_eq!(ProjEdPoint::from(&decoded), point);
+            }
+        }
+        // about half of the points have a representative
+        assert!((16..=48).contains(&representable));
+    }
+}
@@ -0,0 +1,216 @@
+use super::proj_edwards::ProjEdPoint;
+use super::scalar::Scalar;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+// below this number of terms Straus' method is faster than Pippenger's
+const PIPPENGER_THRESHOLD: usize = 190;
+
+impl ProjEdPoint {
+    /// compute sum scalars[i] * points[i] in constant time with respect to the scalars
+    ///
+    /// the number of terms and the points are not secret
+    pub fn multiscalar_mul(scalars: &[Scalar], points: &[Self]) -> Self {
+        assert_eq!(scalars.len(), points.len());
+
+        // table[i][j] = (j + 1) * points[i]
+        let tables = points
+            .iter()
+            .map(|point| {
+                let mut table = [*point; 8];
+                for j in 1..8 {
+                    table[j] = table[j - 1] + point;
+                }
+                table
+            })
+            .collect::<Vec<_>>();
+        let digits = scalars.iter().map(|s| s.to_radix_16()).collect::<Vec<_>>();
+
+        let mut q = Self::identity();
+        for i in (0..64).rev() {
+            q = q.double().double().double().double();
+            for (table, digits) in tables.iter().zip(&digits) {
+                q += &Self::select(table, digits[i]);
+            }
+        }
+        q
+    }
+
+    /// compute sum scalars[i] * points[i] in variable time, only for public scalars
+    ///
+    /// uses Straus' method for few terms and Pippenger's bucket method for many terms
+    pub fn vartime_multiscalar_mul(scalars: &[Scalar], points: &[Self]) -> Self {
+        assert_eq!(scalars.len(), points.len());
+
+        if scalars.len() < PIPPENGER_THRESHOLD {
+            Self::vartime_straus(scalars, points)
+        } else {
+            Self::vartime_pippenger(scalars, points)
+        }
+    }
+
+    // select digit * P from table = [P, 2P, ..., 8P] in constant time, for -8 <= digit <= 8
+    fn select(table: &[Self; 8], digit: i8) -> Self {
+        let negative = (digit as u8) >> 7;
+        let abs = ((digit as u8) ^ 0u8.wrapping_sub(negative)).wrapping_add(negative);
+
+        let mut point = Self::identity();
+        for (j, entry) in (1u8..).zip(table) {
+            point.conditional_assign(entry, abs.ct_eq(&j));
+        }
+        Self::conditional_select(&point, &-point, Choice::from(negative))
+    }
+
+    // width-5 NAF for every scalar, all the terms share the same doublings
+    fn vartime_straus(scalars: &[Scalar], points: &[Self]) -> Self {
+        let nafs = scalars
+            .iter()
+            .map(|s| s.non_adjacent_form(5))
+            .collect::<Vec<_>>();
+
+        // odd multiples P, 3P, ..., 15P
+        let tables = points
+            .iter()
+            .map(|point| {
+                let point2 = point.double();
+                let mut table = [*point; 8];
+                for j in 1..8 {
+                    table[j] = table[j - 1] + &point2;
+                }
+                table
+            })
+            .collect::<Vec<_>>();
+
+        let mut q = Self::identity();
+
+        let Some(top) = (0..256).rev().find(|&i| nafs.iter().any(|naf| naf[i] != 0)) else {
+            return q;
+        };
+
+        for i in (0..=top).rev() {
+            q = q.double();
+            for (naf, table) in nafs.iter().zip(&tables) {
+                match naf[i] {
+                    0 => {}
+                    digit if digit > 0 => q += &table[(digit / 2) as usize],
+                    digit => q -= &table[(-digit / 2) as usize],
+                }
+            }
+        }
+        q
+    }
+
+    // for each radix 2^w digit position, put every point into the bucket of its digit, then
+    // sum the buckets weighted by their index
+    fn vartime_pippenger(scalars: &[Scalar], points: &[Self]) -> Self {
+        let w = match scalars.len() {
+            0..=499 => 6,
+            500..=799 => 7,
+            _ => 8,
+        };
+        let digits_count = 256_usize.div_ceil(w);
+        let buckets_count = 1 << (w - 1);
+
+        let digits = scalars.iter().map(|s| s.to_radix_2w(w)).collect::<Vec<_>>();
+
+        let mut q = Self::identity();
+        for i in (0..digits_count).rev() {
+            for _ in 0..w {
+                q = q.double();
+            }
+
+            // buckets[j] = sum of the points whose digit is +- (j + 1)
+            let mut buckets = vec![Self::identity(); buckets_count];
+            for (digits, point) in digits.iter().zip(points) {
+                match digits[i] {
+                    0 => {}
+                    digit if digit > 0 => buckets[(digit - 1) as usize] += point,
+                    digit => {
+                        let j = (-(digit as i16) - 1) as usize;
+                        buckets[j] -= point;
+                    }
+                }
+            }
+
+            // sum (j + 1) * buckets[j] = buckets[n - 1] + (buckets[n - 1] + buckets[n - 2]) + ...
+            let mut running_sum = Self::identity();
+            let mut column = Self::identity();
+            for bucket in buckets.iter().rev() {
+                running_sum += bucket;
+                column += &running_sum;
+            }
+            q += &column;
+        }
+        q
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha2::{Digest, Sha512};
+
+    fn scalar(i: usize) -> Scalar {
+        Scalar::from_bytes_mod_order_wide(&Sha512::digest(i.to_le_bytes()).into())
+    }
+
+    // scalars a_i and points P_i = b_i * B, with sum a_i * P_i = (sum a_i * b_i) * B
+    fn terms(n: usize) -> (Vec<Scalar>, Vec<ProjEdPoint>, ProjEdPoint) {
+        let scalars = (0..n).map(|i| scalar(2 * i)).collect::<Vec<_>>();
+        let logs = (0..n).map(|i| scalar(2 * i + 1)).collect::<Vec<_>>();
+        let points = logs.iter().map(ProjEdPoint::mul_base).collect();
+        let expected = scalars
+            .iter()
+            .zip(&logs)
+            .fold(Scalar::ZERO, |acc, (&a, &b)| acc + a * b);
+        (scalars, points, ProjEdPoint::mul_base(&expected))
+    }
+
+    #[test]
+    fn correct_multiscalar_mul() {
+        for n in [0, 1, 2, 16] {
+            let (scalars, points, expected) = terms(n);
+            assert_eq!(ProjEdPoint::multiscalar_mul(&scalars, &points), expected);
+        }
+    }
+
+    #[test]
+    fn correct_vartime_multiscalar_mul() {
+        for n in [0, 1, 2, 16, PIPPENGER_THRESHOLD] {
+            let (scalars, points, expected) = terms(n);
+            assert_eq!(
+                ProjEdPoint::vartime_multiscalar_mul(&scalars, &points),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn equivalent_straus_pippenger() {
+        for n in [1, 3, 64] {
+            let (scalars, points, expected) = terms(n);
+            assert_eq!(ProjEdPoint::vartime_straus(&scalars, &points), expected);
+            assert_eq!(ProjEdPoint::vartime_pippenger(&scalars, &points), expected);
+        }
+    }
+
+    #[test]
+    fn correct_pippenger_windows() {
+        let (scalars, points, expected) = terms(800);
+        assert_eq!(ProjEdPoint::vartime_pippenger(&scalars, &points), expected);
+        assert_eq!(
+            ProjEdPoint::vartime_pippenger(&scalars[..500], &points[..500]),
+            ProjEdPoint::vartime_straus(&scalars[..500], &points[..500])
+        );
+    }
+
+    #[test]
+    fn correct_extreme_scalars() {
+        let b = ProjEdPoint::mul_base(&Scalar::ONE);
+        let scalars = [-Scalar::ONE, Scalar::ZERO, Scalar::ONE];
+        let points = [b, b.double(), b];
+        let identity = ProjEdPoint::identity();
+        assert_eq!(ProjEdPoint::multiscalar_mul(&scalars, &points), identity);
+        assert_eq!(ProjEdPoint::vartime_straus(&scalars, &points), identity);
+        assert_eq!(ProjEdPoint::vartime_pippenger(&scalars, &points), identity);
+    }
+}
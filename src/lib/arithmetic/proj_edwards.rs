@@ -1,235 +1,629 @@
-use super::xline_proj_montgomery::scalar_mul;
-use super::{super::Key, montgomery::MPoint, proj_montgomery::ProjMPoint};
-use rug::{integer::Order, ops::Pow, Integer};
-
-fn get_d() -> Integer {
-    Integer::from_str_radix(
-        "37095705934669439343138083508754565189542113879843219016388785533085940283555",
-        10,
-    )
-    .unwrap()
+use super::basepoint_table::{basepoint_odd_multiples, basepoint_table, AffineNielsPoint};
+use super::curve::{Curve25519, CurveParams};
+use super::field::FieldElement;
+use super::scalar::Scalar;
+use super::{super::Error, super::Key, montgomery::MPoint, proj_montgomery::ProjMPoint};
+use std::marker::PhantomData;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+// x * y of the base point of edwards25519, precomputed so that `basepoint` is a const fn
+const BASEPOINT_T: FieldElement = FieldElement::from_limbs([
+    1_841_354_044_333_475,
+    16_398_895_984_059,
+    755_974_180_946_558,
+    900_171_276_175_154,
+    1_821_297_809_914_039,
+]);
+
+/// the 8 points of order dividing 8: EIGHT_TORSION[i] = i * T, where T has order 8
+pub const EIGHT_TORSION: [ProjEdPoint; 8] = [
+    ProjEdPoint::identity(),
+    // 1 * T
+    ProjEdPoint::from_affine_limbs(
+        [
+            358_744_748_052_810,
+            1_691_584_618_240_980,
+            977_650_209_285_361,
+            1_429_865_912_637_724,
+            560_044_844_278_676,
+        ],
+        [
+            84_926_274_344_903,
+            473_620_666_599_931,
+            365_590_438_845_504,
+            1_028_470_286_882_429,
+            2_146_499_180_330_972,
+        ],
+        [
+            1_448_326_834_587_521,
+            1_857_896_831_960_481,
+            1_093_722_731_865_333,
+            1_677_408_490_711_241,
+            1_915_505_153_018_406,
+        ],
+    ),
+    // 2 * T
+    ProjEdPoint::from_affine_limbs(
+        [
+            533_094_393_274_173,
+            2_016_890_930_128_738,
+            18_285_341_111_199,
+            134_597_186_663_265,
+            1_486_323_764_102_114,
+        ],
+        [0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0],
+    ),
+    // 3 * T
+    ProjEdPoint::from_affine_limbs(
+        [
+            358_744_748_052_810,
+            1_691_584_618_240_980,
+            977_650_209_285_361,
+            1_429_865_912_637_724,
+            560_044_844_278_676,
+        ],
+        [
+            2_166_873_539_340_326,
+            1_778_179_147_085_316,
+            1_886_209_374_839_743,
+            1_223_329_526_802_818,
+            105_300_633_354_275,
+        ],
+        [
+            803_472_979_097_708,
+            393_902_981_724_766,
+            1_158_077_081_819_914,
+            574_391_322_974_006,
+            336_294_660_666_841,
+        ],
+    ),
+    // 4 * T
+    ProjEdPoint::from_affine_limbs(
+        [0, 0, 0, 0, 0],
+        [
+            2_251_799_813_685_228,
+            2_251_799_813_685_247,
+            2_251_799_813_685_247,
+            2_251_799_813_685_247,
+            2_251_799_813_685_247,
+        ],
+        [0, 0, 0, 0, 0],
+    ),
+    // 5 * T
+    ProjEdPoint::from_affine_limbs(
+        [
+            1_893_055_065_632_419,
+            560_215_195_444_267,
+            1_274_149_604_399_886,
+            821_933_901_047_523,
+            1_691_754_969_406_571,
+        ],
+        [
+            2_166_873_539_340_326,
+            1_778_179_147_085_316,
+            1_886_209_374_839_743,
+            1_223_329_526_802_818,
+            105_300_633_354_275,
+        ],
+        [
+            1_448_326_834_587_521,
+            1_857_896_831_960_481,
+            1_093_722_731_865_333,
+            1_677_408_490_711_241,
+            1_915_505_153_018_406,
+        ],
+    ),
+    // 6 * T
+    ProjEdPoint::from_affine_limbs(
+        [
+            1_718_705_420_411_056,
+            234_908_883_556_509,
+            2_233_514_472_574_048,
+            2_117_202_627_021_982,
+            765_476_049_583_133,
+        ],
+        [0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0],
+    ),
+    // 7 * T
+    ProjEdPoint::from_affine_limbs(
+        [
+            1_893_055_065_632_419,
+            560_215_195_444_267,
+            1_274_149_604_399_886,
+            821_933_901_047_523,
+            1_691_754_969_406_571,
+        ],
+        [
+            84_926_274_344_903,
+            473_620_666_599_931,
+            365_590_438_845_504,
+            1_028_470_286_882_429,
+            2_146_499_180_330_972,
+        ],
+        [
+            803_472_979_097_708,
+            393_902_981_724_766,
+            1_158_077_081_819_914,
+            574_391_322_974_006,
+            336_294_660_666_841,
+        ],
+    ),
+];
+
+/// point of the twisted Edwards curve of C in extended coordinates (x : y : z : t), with
+/// x / z and y / z the affine coordinates and x * y = z * t
+#[derive(Clone, Copy, Debug)]
+pub struct ProjEdPoint<C: CurveParams = Curve25519> {
+    x: FieldElement,
+    y: FieldElement,
+    z: FieldElement,
+    t: FieldElement,
+    curve: PhantomData<C>,
 }
 
-pub fn get_l() -> Integer {
-    Integer::from(2).pow(252)
-        + Integer::from_str_radix("27742317777372353535851937790883648493", 10).unwrap()
-}
-
-pub fn get_b() -> ProjEdPoint {
-    let x = Integer::from_str_radix(
-        "15112221349535400772501151409588531511454012693041857206046113283949847762202",
-        10,
-    )
-    .unwrap();
-    let y = Integer::from_str_radix(
-        "46316835694926478169428394003475163141307993866256225615783033603165251855960",
-        10,
-    )
-    .unwrap();
-    ProjEdPoint::new(x, y)
-}
-
-#[derive(Clone, Debug)]
-pub struct ProjEdPoint {
-    x: Integer,
-    y: Integer,
-    z: Integer,
-    t: Integer,
-}
-
-impl ProjEdPoint {
-    pub fn new(x: Integer, y: Integer) -> Self {
-        let p = Self::p();
+impl<C: CurveParams> ProjEdPoint<C> {
+    pub fn new(x: FieldElement, y: FieldElement) -> Self {
         Self {
-            x: x.clone().modulo(&p),
-            y: y.clone().modulo(&p),
-            z: Integer::from(1),
-            t: (x * y).modulo(&p),
+            x,
+            y,
+            z: FieldElement::ONE,
+            t: x * y,
+            curve: PhantomData,
         }
     }
 
-    pub const fn x(&self) -> &Integer {
+    pub const fn x(&self) -> &FieldElement {
         &self.x
     }
 
-    pub const fn y(&self) -> &Integer {
+    pub const fn y(&self) -> &FieldElement {
         &self.y
     }
 
-    pub const fn z(&self) -> &Integer {
+    pub const fn z(&self) -> &FieldElement {
         &self.z
     }
 
-    pub fn p() -> Integer {
-        Integer::from(2).pow(255) - Integer::from(19)
+    pub const fn t(&self) -> &FieldElement {
+        &self.t
     }
 
-    pub fn a() -> Integer {
-        Integer::from(486_662)
+    /// point from extended coordinates (x : y : z : t), with x * y = z * t
+    pub(crate) const fn from_extended(
+        x: FieldElement,
+        y: FieldElement,
+        z: FieldElement,
+        t: FieldElement,
+    ) -> Self {
+        Self {
+            x,
+            y,
+            z,
+            t,
+            curve: PhantomData,
+        }
     }
 
-    pub fn b() -> Integer {
-        Integer::from(1)
+    // (x, y) with t = x * y given as limbs, for constant tables
+    const fn from_affine_limbs(x: [u64; 5], y: [u64; 5], t: [u64; 5]) -> Self {
+        Self {
+            x: FieldElement::from_limbs(x),
+            y: FieldElement::from_limbs(y),
+            z: FieldElement::ONE,
+            t: FieldElement::from_limbs(t),
+            curve: PhantomData,
+        }
     }
 
-    // for birational equivalence between Edwards and Montgomery curves
-    pub fn root_minus_a_minus_2_mod_p() -> Integer {
-        Integer::from_str_radix(
-            "6853475219497561581579357271197624642482790079785650197046958215289687604742",
-            10,
-        )
-        .unwrap()
+    /// neutral element (0, 1) of the curve C, `identity` for edwards25519
+    pub const fn generic_identity() -> Self {
+        Self {
+            x: FieldElement::ZERO,
+            y: FieldElement::ONE,
+            z: FieldElement::ONE,
+            t: FieldElement::ZERO,
+            curve: PhantomData,
+        }
+    }
+
+    /// base point of the curve C, `basepoint` for edwards25519
+    pub fn generic_basepoint() -> Self {
+        Self::new(C::BASEPOINT_X, C::BASEPOINT_Y)
+    }
+
+    pub fn is_identity(&self) -> bool {
+        (self.x.is_zero() & self.y.ct_eq(&self.z)).into()
+    }
+
+    /// compute 2 * self using the dedicated doubling formulas from RFC 8032 (section 5.1.4)
+    pub fn double(&self) -> Self {
+        let ta = self.x.square();
+        let tb = self.y.square();
+        let tc = self.z.square() + self.z.square();
+        let th = ta + tb;
+        let te = th - (self.x + self.y).square();
+        let tg = ta - tb;
+        let tf = tc + tg;
+
+        Self {
+            x: te * tf,
+            y: tg * th,
+            z: tf * tg,
+            t: te * th,
+            curve: PhantomData,
+        }
     }
 
     fn normalize(&mut self) {
-        let p = Self::p();
-        let z_inv = self.z.clone().secure_pow_mod(&(p.clone() - 2), &p);
-        self.x = (self.x.clone() * &z_inv).modulo(&p);
-        self.y = (self.y.clone() * &z_inv).modulo(&p);
-        self.z = Integer::from(1);
-        self.t = (self.x.clone() * &self.y).modulo(&p);
+        let z_inv = self.z.invert();
+        self.x = self.x * z_inv;
+        self.y = self.y * z_inv;
+        self.z = FieldElement::ONE;
+        self.t = self.x * self.y;
     }
 
     pub fn encode(&mut self) -> Key {
         self.normalize();
-        let mut digits: Key = {
-            let mut digits = self.y.to_digits(Order::Lsf);
-            digits.resize(32, 0);
-            digits.try_into().unwrap()
-        };
-        digits[31] |= (u8::from(self.x.get_bit(0))) << 7;
+        let mut digits = self.y.to_bytes();
+        digits[31] |= self.x.is_negative().unwrap_u8() << 7;
         digits
     }
 
-    fn recover_x(y: Integer, sign: bool) -> Result<Integer, ()> {
-        let p = Self::p();
-        if y >= p {
-            Err(())
+    fn recover_x(y: FieldElement, sign: bool) -> Result<FieldElement, Error> {
+        let y2 = y.square();
+        let u = y2 - FieldElement::ONE;
+        let v = C::D * y2 + FieldElement::ONE;
+
+        // x is non-negative at this point
+        let (was_square, mut x) = FieldElement::sqrt_ratio_i(&u, &v);
+        if !bool::from(was_square) {
+            return Err(Error::NotOnCurve);
+        }
+
+        if bool::from(x.is_zero()) && sign {
+            Err(Error::NonCanonicalX)
         } else {
-            let d: Integer = get_d();
-            let two = Integer::from(2);
-
-            let y2: Integer = y.secure_pow_mod(&two, &p);
-            let u: Integer = (y2.clone() - Integer::ONE).modulo(&p);
-            let v: Integer = (d * y2 + Integer::ONE).modulo(&p);
-
-            let mut x = {
-                let a = (u.clone() * v.clone().secure_pow_mod(&Integer::from(3), &p)).modulo(&p);
-                let b = (u.clone() * v.clone().secure_pow_mod(&Integer::from(7), &p))
-                    .secure_pow_mod(&((p.clone() - 5) / 8), &p);
-                (a * b).modulo(&p)
-            };
-
-            let vx2 = (v * x.clone().secure_pow_mod(&two, &p)).modulo(&p);
-
-            if vx2.is_congruent(&u, &p) {
-            } else if vx2.is_congruent(&(-u), &p) {
-                x = (x * Integer::from(2).secure_pow_mod(&((p.clone() - 1) / 4), &p)).modulo(&p);
-            } else {
-                return Err(());
-            };
-
-            if x == Integer::ZERO && sign {
-                Err(())
-            } else {
-                if !(x.is_congruent_u(u32::from(sign), 2)) {
-                    x = -x + &p;
-                }
-                Ok(x)
+            if sign {
+                x = -x;
             }
+            Ok(x)
         }
     }
 
-    pub fn decode(digits: Key) -> Result<Self, ()> {
-        let mut y = Integer::from_digits(&digits, Order::Lsf);
-        let sign = y.get_bit(255);
-        y.set_bit(255, false);
+    /// decode a point of the curve C, `decode` for edwards25519
+    ///
+    /// fails with `NonCanonicalY`, `NotOnCurve` or `NonCanonicalX`
+    pub fn generic_decode(digits: Key) -> Result<Self, Error> {
+        let sign = digits[31] >> 7 == 1;
+        let mut y_digits = digits;
+        y_digits[31] &= 0b0111_1111;
+        let y = FieldElement::from_bytes(&y_digits);
+
+        // reject y >= p
+        if y.to_bytes() != y_digits {
+            return Err(Error::NonCanonicalY);
+        }
 
-        match Self::recover_x(y.clone(), sign) {
-            Ok(x) => Ok(Self {
-                x: x.clone(),
-                y: y.clone(),
-                z: Integer::from(1),
-                t: (x * &y).modulo(&Self::p()),
-            }),
-            Err(()) => Err(()),
+        let x = Self::recover_x(y, sign)?;
+        Ok(Self::new(x, y))
+    }
+}
+
+// the constructors of the default curve are only defined for Curve25519, so that
+// `ProjEdPoint::basepoint()` needs no type annotation (as `HashMap::new` for the default hasher)
+impl ProjEdPoint {
+    /// neutral element (0, 1)
+    pub const fn identity() -> Self {
+        Self::generic_identity()
+    }
+
+    /// base point B of edwards25519
+    pub const fn basepoint() -> Self {
+        Self::from_extended(
+            Curve25519::BASEPOINT_X,
+            Curve25519::BASEPOINT_Y,
+            FieldElement::ONE,
+            BASEPOINT_T,
+        )
+    }
+
+    /// fails with `NonCanonicalY`, `NotOnCurve` or `NonCanonicalX`
+    pub fn decode(digits: Key) -> Result<Self, Error> {
+        Self::generic_decode(digits)
+    }
+
+    /// decode without rejecting the non-canonical encodings: y >= p is reduced modulo p, and
+    /// x = 0 with the sign bit set is read as x = 0, as required by ZIP-215
+    pub fn decode_non_canonical(digits: Key) -> Result<Self, Error> {
+        let sign = digits[31] >> 7 == 1;
+        let mut y_digits = digits;
+        y_digits[31] &= 0b0111_1111;
+        let y = FieldElement::from_bytes(&y_digits);
+
+        // recover_x only fails with the sign bit set if x = 0 or if no x exists
+        let x = Self::recover_x(y, sign).or_else(|_| Self::recover_x(y, false))?;
+        Ok(Self::new(x, y))
+    }
+}
+
+// the scalars and the cofactor are the ones of edwards25519
+impl ProjEdPoint {
+    /// compute 8 * self, which maps every point to the prime-order subgroup
+    pub fn mul_by_cofactor(&self) -> Self {
+        self.double().double().double()
+    }
+
+    /// true iff self is one of the 8 points of `EIGHT_TORSION`
+    pub fn is_small_order(&self) -> bool {
+        self.mul_by_cofactor().is_identity()
+    }
+
+    /// true iff self is in the prime-order subgroup generated by B, i.e. L * self = 0
+    ///
+    /// runs in variable time, self is assumed to be public
+    pub fn is_torsion_free(&self) -> bool {
+        // L * P = (L - 1) * P + P, and L - 1 = -1 mod L
+        let l_minus_1_times_self =
+            Self::vartime_double_scalar_mul_basepoint(&-Scalar::ONE, self, &Scalar::ZERO);
+        (l_minus_1_times_self + self).is_identity()
+    }
+
+    /// compute a * A + b * B, where B is the base point
+    ///
+    /// uses Straus' method with width-5 NAF for A and width-8 NAF for B, so the running time
+    /// depends on a and b: only use it with public scalars, e.g. to verify signatures
+    pub fn vartime_double_scalar_mul_basepoint(a: &Scalar, a_point: &Self, b: &Scalar) -> Self {
+        let a_naf = a.non_adjacent_form(5);
+        let b_naf = b.non_adjacent_form(8);
+
+        // odd multiples A, 3A, ..., 15A
+        let a_table = {
+            let a2 = a_point.double();
+            let mut table = [*a_point; 8];
+            for i in 1..8 {
+                table[i] = table[i - 1] + &a2;
+            }
+            table
+        };
+        let b_table = basepoint_odd_multiples();
+
+        let mut q = Self::identity();
+
+        let Some(top) = (0..256).rev().find(|&i| a_naf[i] != 0 || b_naf[i] != 0) else {
+            return q;
+        };
+
+        for i in (0..=top).rev() {
+            q = q.double();
+
+            match a_naf[i] {
+                0 => {}
+                digit if digit > 0 => q += &a_table[(digit / 2) as usize],
+                digit => q -= &a_table[(-digit / 2) as usize],
+            }
+
+            match b_naf[i] {
+                0 => {}
+                digit if digit > 0 => q += &b_table[(digit / 2) as usize],
+                digit => q -= &b_table[(-digit / 2) as usize],
+            }
+        }
+        q
+    }
+
+    /// compute s * B in constant time, using the precomputed table of multiples of B
+    pub fn mul_base(s: &Scalar) -> Self {
+        let table = basepoint_table();
+        let mut q = Self::identity();
+        for (i, &digit) in s.to_radix_16().iter().enumerate() {
+            q += &table.select(i, digit);
         }
+        q
     }
 }
 
-impl std::ops::Add<&Self> for ProjEdPoint {
+impl<C: CurveParams> std::ops::Add<&Self> for ProjEdPoint<C> {
     type Output = Self;
 
     fn add(self, other: &Self) -> Self {
-        let p = Self::p();
-        let ta = ((self.y.clone() - &self.x) * (other.y.clone() - &other.x)).modulo(&p);
-        let tb = ((self.y + &self.x) * (other.y.clone() + &other.x)).modulo(&p);
-        let tc = (Integer::from(2) * &self.t * &other.t * get_d()).modulo(&p);
-        let td = (Integer::from(2) * &self.z * &other.z).modulo(&p);
-        let te = (tb.clone() - &ta).modulo(&p);
-        let tf = (td.clone() - &tc).modulo(&p);
-        let tg = (td + tc).modulo(&p);
-        let th = (tb + ta).modulo(&p);
+        let ta = (self.y - self.x) * (other.y - other.x);
+        let tb = (self.y + self.x) * (other.y + other.x);
+        let tc = self.t * (C::D + C::D) * other.t;
+        let td = (self.z + self.z) * other.z;
+        let te = tb - ta;
+        let tf = td - tc;
+        let tg = td + tc;
+        let th = tb + ta;
+
+        Self {
+            x: te * tf,
+            y: tg * th,
+            z: tf * tg,
+            t: te * th,
+            curve: PhantomData,
+        }
+    }
+}
+
+impl<C: CurveParams> std::ops::AddAssign<&Self> for ProjEdPoint<C> {
+    fn add_assign(&mut self, other: &Self) {
+        *self = *self + other;
+    }
+}
+
+// -(x, y) = (-x, y)
+impl<C: CurveParams> std::ops::Neg for ProjEdPoint<C> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            x: -self.x,
+            y: self.y,
+            z: self.z,
+            t: -self.t,
+            curve: PhantomData,
+        }
+    }
+}
+
+impl<C: CurveParams> std::ops::Sub<&Self> for ProjEdPoint<C> {
+    type Output = Self;
+
+    fn sub(self, other: &Self) -> Self {
+        self + &(-*other)
+    }
+}
+
+impl<C: CurveParams> std::ops::SubAssign<&Self> for ProjEdPoint<C> {
+    fn sub_assign(&mut self, other: &Self) {
+        *self = *self - other;
+    }
+}
+
+impl<C: CurveParams> std::iter::Sum for ProjEdPoint<C> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::generic_identity(), |acc, point| acc + &point)
+    }
+}
+
+impl<'a, C: CurveParams> std::iter::Sum<&'a Self> for ProjEdPoint<C> {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.fold(Self::generic_identity(), |acc, point| acc + point)
+    }
+}
+
+// same formulas as above, with z2 = 1 and t2 * 2d precomputed
+impl std::ops::Add<&AffineNielsPoint> for ProjEdPoint {
+    type Output = Self;
+
+    fn add(self, other: &AffineNielsPoint) -> Self {
+        let ta = (self.y - self.x) * other.y_minus_x;
+        let tb = (self.y + self.x) * other.y_plus_x;
+        let tc = self.t * other.xy2d;
+        let td = self.z + self.z;
+        let te = tb - ta;
+        let tf = td - tc;
+        let tg = td + tc;
+        let th = tb + ta;
 
         Self {
-            x: (te.clone() * &tf).modulo(&p),
-            y: (tg.clone() * &th).modulo(&p),
-            z: (tf * tg).modulo(&p),
-            t: (te * th).modulo(&p),
+            x: te * tf,
+            y: tg * th,
+            z: tf * tg,
+            t: te * th,
+            curve: PhantomData,
         }
     }
 }
 
-// use Montgomery ladder to compute scalar multiplication in constant time
-impl std::ops::Mul<&Integer> for ProjEdPoint {
+impl std::ops::AddAssign<&AffineNielsPoint> for ProjEdPoint {
+    fn add_assign(&mut self, other: &AffineNielsPoint) {
+        *self = *self + other;
+    }
+}
+
+impl std::ops::Sub<&AffineNielsPoint> for ProjEdPoint {
+    type Output = Self;
+
+    fn sub(self, other: &AffineNielsPoint) -> Self {
+        self + &-*other
+    }
+}
+
+impl std::ops::SubAssign<&AffineNielsPoint> for ProjEdPoint {
+    fn sub_assign(&mut self, other: &AffineNielsPoint) {
+        *self = *self - other;
+    }
+}
+
+// constant-time ladder on the Edwards coordinates, with the complete addition formulas, so that
+// it works for every point (the identity and the points of small order included)
+//
+// scalars are reduced modulo the order L of edwards25519, so it is only defined on that curve
+impl std::ops::Mul<&Scalar> for ProjEdPoint {
     type Output = Self;
 
     // self is P, other is s, output is Q
-    fn mul(self, s: &Integer) -> Self {
-        let proj_m_point = scalar_mul(
-            &Self::p(),
-            &Self::a(),
-            &Self::b(),
-            s,
-            &MPoint::try_from(&self).unwrap(),
-        );
-        let mut proj_ed_point = Self::from(&proj_m_point);
-        proj_ed_point.normalize();
-        Self::from(&proj_m_point)
+    fn mul(self, s: &Scalar) -> Self {
+        let bytes = s.to_bytes();
+        let mut r0 = Self::identity();
+        let mut r1 = self;
+        let mut swap = Choice::from(0);
+        for i in (0..256).rev() {
+            let bit = Choice::from((bytes[i / 8] >> (i % 8)) & 1);
+            swap ^= bit;
+            Self::conditional_swap(&mut r0, &mut r1, swap);
+            swap = bit;
+            r1 = r0 + &r1;
+            r0 = r0.double();
+        }
+        Self::conditional_swap(&mut r0, &mut r1, swap);
+        r0
     }
 }
 
-impl PartialEq for ProjEdPoint {
+impl<C: CurveParams> ConditionallySelectable for ProjEdPoint<C> {
+    /// return b if choice is 1 and a otherwise, without branching on choice
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self {
+            x: FieldElement::conditional_select(&a.x, &b.x, choice),
+            y: FieldElement::conditional_select(&a.y, &b.y, choice),
+            z: FieldElement::conditional_select(&a.z, &b.z, choice),
+            t: FieldElement::conditional_select(&a.t, &b.t, choice),
+            curve: PhantomData,
+        }
+    }
+}
+
+impl<C: CurveParams> ConstantTimeEq for ProjEdPoint<C> {
+    /// compare x / z and y / z without inversion, and without branching on the coordinates
+    fn ct_eq(&self, other: &Self) -> Choice {
+        (self.x * other.z).ct_eq(&(other.x * self.z))
+            & (self.y * other.z).ct_eq(&(other.y * self.z))
+    }
+}
+
+impl<C: CurveParams> PartialEq for ProjEdPoint<C> {
     fn eq(&self, other: &Self) -> bool {
-        let p = Self::p();
-        (self.x.clone() * &other.z - other.x.clone() * &self.z).modulo(&p) == Integer::ZERO
-            && (self.y.clone() * &other.z - other.y.clone() * &self.z).modulo(&p) == Integer::ZERO
+        self.ct_eq(other).into()
     }
 }
 
 // x = x (x + z), y = y (x - z), z = y (x + z), t = x (x - z)
-impl From<&ProjMPoint> for ProjEdPoint {
-    fn from(point: &ProjMPoint) -> Self {
-        let p = Self::p();
-        let root = Self::root_minus_a_minus_2_mod_p();
-        let x = (((point.x().clone() + point.z()) * point.x()).modulo(&p) * &root).modulo(&p);
-        let y = ((point.x().clone() - point.z()) * point.y()).modulo(&p);
-        let z = ((point.x().clone() + point.z()) * point.y()).modulo(&p);
-        let t = (((point.x().clone() - point.z()) * point.x()).modulo(&p) * &root).modulo(&p);
-        Self { x, y, z, t }
+impl<C: CurveParams> From<&ProjMPoint<C>> for ProjEdPoint<C> {
+    fn from(point: &ProjMPoint<C>) -> Self {
+        let root = C::ROOT_MINUS_A_MINUS_2;
+        let x = (*point.x() + *point.z()) * *point.x() * root;
+        let y = (*point.x() - *point.z()) * *point.y();
+        let z = (*point.x() + *point.z()) * *point.y();
+        let t = (*point.x() - *point.z()) * *point.x() * root;
+        Self {
+            x,
+            y,
+            z,
+            t,
+            curve: PhantomData,
+        }
     }
 }
 
 // x = x (x + 1), y = y (x - 1), z = y (x + 1), t = x (x - 1)
-impl From<&MPoint> for ProjEdPoint {
-    fn from(point: &MPoint) -> Self {
-        let p = Self::p();
-        let root = Self::root_minus_a_minus_2_mod_p();
-        let x = ((point.x().clone() + Integer::from(1)) * point.x() * &root).modulo(&p);
-        let y = ((point.x().clone() - Integer::from(1)) * point.y()).modulo(&p);
-        let z = ((point.x().clone() + Integer::from(1)) * point.y()).modulo(&p);
-        let t = ((point.x().clone() - Integer::from(1)) * point.x() * &root).modulo(&p);
-        Self { x, y, z, t }
+impl<C: CurveParams> From<&MPoint<C>> for ProjEdPoint<C> {
+    fn from(point: &MPoint<C>) -> Self {
+        let root = C::ROOT_MINUS_A_MINUS_2;
+        let x = (*point.x() + FieldElement::ONE) * *point.x() * root;
+        let y = (*point.x() - FieldElement::ONE) * *point.y();
+        let z = (*point.x() + FieldElement::ONE) * *point.y();
+        let t = (*point.x() - FieldElement::ONE) * *point.x() * root;
+        Self {
+            x,
+            y,
+            z,
+            t,
+            curve: PhantomData,
+        }
     }
 }
 
@@ -237,21 +631,32 @@ impl From<&MPoint> for ProjEdPoint {
 mod test {
     use super::*;
 
+    fn bytes_from_str(s: &str) -> Key {
+        s.chars()
+            .collect::<Vec<char>>()
+            .chunks(2)
+            .map(|chunk| chunk.iter().collect::<String>())
+            .map(|byte| u8::from_str_radix(&byte, 16).unwrap())
+            .collect::<Vec<u8>>()
+            .try_into()
+            .unwrap()
+    }
+
     #[test]
     fn correct_from_mpoint() {
-        let b = get_b();
+        let b: ProjEdPoint = ProjEdPoint::basepoint();
         assert_eq!(ProjEdPoint::from(&MPoint::try_from(&b).unwrap()), b);
     }
 
     #[test]
     fn correct_from_proj_mpoint() {
-        let b = get_b();
+        let b: ProjEdPoint = ProjEdPoint::basepoint();
         assert_eq!(ProjEdPoint::from(&ProjMPoint::from(&b)), b);
     }
 
     #[test]
     fn correct_from_cycle() {
-        let b = get_b();
+        let b: ProjEdPoint = ProjEdPoint::basepoint();
         assert_eq!(
             ProjEdPoint::from(&ProjMPoint::from(&MPoint::try_from(&b).unwrap())),
             b
@@ -260,71 +665,275 @@ mod test {
 
     #[test]
     fn correct_from_reverse_cycle() {
-        let b = get_b();
+        let b: ProjEdPoint = ProjEdPoint::basepoint();
         assert_eq!(
             ProjEdPoint::from(&MPoint::try_from(&ProjMPoint::from(&b)).unwrap()),
             b
         );
     }
 
-    fn equivalent_mul(s: Integer) {
-        let b = get_b();
-        assert_eq!(b.clone() * &s, b.edwards_mul(s));
+    #[test]
+    fn correct_b_coordinates() {
+        let b: ProjEdPoint = ProjEdPoint::basepoint();
+        assert_eq!(b, ProjEdPoint::new(*b.x(), *b.y()));
+        let mut encoded = b;
+        assert_eq!(ProjEdPoint::decode(encoded.encode()).unwrap(), b);
+    }
+
+    #[test]
+    fn correct_curve_constants() {
+        assert_eq!(
+            Curve25519::D,
+            -FieldElement::from(121_665) * FieldElement::from(121_666).invert()
+        );
+        assert_eq!(
+            BASEPOINT_T,
+            Curve25519::BASEPOINT_X * Curve25519::BASEPOINT_Y
+        );
+        assert_eq!(
+            Curve25519::ROOT_MINUS_A_MINUS_2.square(),
+            -(Curve25519::A + FieldElement::from(2))
+        );
+    }
+
+    fn equivalent_mul(s: Scalar) {
+        let b: ProjEdPoint = ProjEdPoint::basepoint();
+        assert_eq!(b * &s, b.edwards_mul(&s.to_bytes()));
+    }
+
+    #[test]
+    fn correct_mul_edge_cases() {
+        let b: ProjEdPoint = ProjEdPoint::basepoint();
+        let identity: ProjEdPoint = ProjEdPoint::identity();
+        let s = Scalar::from(1234);
+
+        assert!((b * &Scalar::ZERO).is_identity());
+        assert!((identity * &s).is_identity());
+        assert!((identity * &Scalar::ZERO).is_identity());
+        assert_eq!(b * &Scalar::ONE, b);
+
+        // i * T has order dividing 8
+        for (i, t) in EIGHT_TORSION.iter().enumerate() {
+            assert!((*t * &Scalar::from(8)).is_identity());
+            assert_eq!(*t * &Scalar::ONE, *t);
+            assert_eq!(*t * &Scalar::from(3), EIGHT_TORSION[3 * i % 8]);
+            assert_eq!((b + t) * &s, b * &s + &EIGHT_TORSION[1234 * i % 8]);
+        }
     }
 
     // implement scalar multiplication using Edwards formulas from RFC 8032
     // only for debug purposes, not used in release binaries
     impl ProjEdPoint {
-        fn double(&mut self) {
-            let p = Self::p();
-            let two = Integer::from(2);
-            let ta = self.x.clone().secure_pow_mod(&two, &p);
-            let tb = self.y.clone().secure_pow_mod(&two, &p);
-            let tc = self.z.clone().secure_pow_mod(&two, &p) * &two;
-            let th = (ta.clone() + &tb).modulo(&p);
-            let te = (-(self.x.clone() + &self.y).secure_pow_mod(&two, &p) + &th).modulo(&p);
-            let tg = (ta - tb).modulo(&p);
-            let tf = (tc + &tg).modulo(&p);
-            let x = (te.clone() * &tf).modulo(&p);
-            let y = (tg.clone() * &th).modulo(&p);
-            let z = (tf * tg).modulo(&p);
-            let t = (te * th).modulo(&p);
-            (self.x, self.y, self.z, self.t) = (x, y, z, t);
-        }
-
-        // self is P, other is s, output is Q
-        fn edwards_mul(mut self, mut s: Integer) -> Self {
-            let mut q = Self {
-                x: Integer::from(0),
-                y: Integer::from(1),
-                z: Integer::from(1),
-                t: Integer::from(0),
-            }; // neutral element
-            while s > Integer::ZERO {
-                if s.get_bit(0) {
-                    q = q + &self;
+        // self is P, s is a little-endian scalar, output is Q
+        fn edwards_mul(mut self, s: &[u8; 32]) -> Self {
+            let mut q = Self::identity();
+            for i in 0..256 {
+                if (s[i / 8] >> (i % 8)) & 1 == 1 {
+                    q += &self;
                 }
-                self.double();
-                s >>= 1;
+                self = self.double();
             }
             q.normalize();
             q
         }
     }
 
+    #[test]
+    fn correct_double() {
+        let b: ProjEdPoint = ProjEdPoint::basepoint();
+        assert_eq!(b.double(), b + &b);
+        assert_eq!(b.double().double(), b + &b + &b + &b);
+        assert!(ProjEdPoint::<Curve25519>::identity().double().is_identity());
+    }
+
+    #[test]
+    fn correct_identity() {
+        // no type annotation is needed for the edwards25519 constructors
+        let b = ProjEdPoint::basepoint();
+        assert!(ProjEdPoint::identity().is_identity());
+        assert!(!b.is_identity());
+        assert_eq!(b + &ProjEdPoint::identity(), b);
+        assert!((b - &b).is_identity());
+        assert!((b + &(-b)).is_identity());
+    }
+
+    #[test]
+    fn correct_ct_eq() {
+        let b: ProjEdPoint = ProjEdPoint::basepoint();
+        // the same point with z = 2
+        let two = FieldElement::from(2);
+        let scaled = ProjEdPoint::from_extended(*b.x() * two, *b.y() * two, two, *b.t() * two);
+        assert!(bool::from(b.ct_eq(&scaled)));
+        assert!(!bool::from(b.ct_eq(&b.double())));
+        assert!(!bool::from(b.ct_eq(&-b)));
+    }
+
+    #[test]
+    fn correct_conditional_select() {
+        let b: ProjEdPoint = ProjEdPoint::basepoint();
+        let b2 = b.double();
+        assert_eq!(ProjEdPoint::conditional_select(&b, &b2, Choice::from(0)), b);
+        assert_eq!(
+            ProjEdPoint::conditional_select(&b, &b2, Choice::from(1)),
+            b2
+        );
+    }
+
+    #[test]
+    fn correct_neg_sub() {
+        let b: ProjEdPoint = ProjEdPoint::basepoint();
+        let b3 = b.double() + &b;
+        assert_eq!(b3 - &b, b.double());
+        assert_eq!(-(-b3), b3);
+        assert_eq!(-b + &b3, b.double());
+    }
+
+    #[test]
+    fn correct_sum() {
+        let b: ProjEdPoint = ProjEdPoint::basepoint();
+        let points = [b, b.double(), -b, b];
+        let mut expected = ProjEdPoint::identity();
+        for point in &points {
+            expected += point;
+        }
+        assert_eq!(points.iter().sum::<ProjEdPoint>(), expected);
+        assert_eq!(points.into_iter().sum::<ProjEdPoint>(), b.double() + &b);
+        assert!(std::iter::empty::<ProjEdPoint>()
+            .sum::<ProjEdPoint>()
+            .is_identity());
+    }
+
+    #[test]
+    fn correct_eight_torsion() {
+        let t = EIGHT_TORSION[1];
+        let mut multiple = ProjEdPoint::identity();
+        for point in &EIGHT_TORSION {
+            assert_eq!(*point, multiple);
+            assert_eq!(*point, ProjEdPoint::new(*point.x(), *point.y()));
+            assert!(point.is_small_order());
+            assert!(point.mul_by_cofactor().is_identity());
+            assert_eq!(point.is_torsion_free(), point.is_identity());
+            multiple += &t;
+        }
+        assert!(multiple.is_identity());
+        assert!(!t.double().double().is_identity());
+    }
+
+    #[test]
+    fn correct_eight_torsion_encodings() {
+        let encodings = [
+            "0100000000000000000000000000000000000000000000000000000000000000",
+            "c7176a703d4dd84fba3c0b760d10670f2a2053fa2c39ccc64ec7fd7792ac037a",
+            "0000000000000000000000000000000000000000000000000000000000000080",
+            "26e8958fc2b227b045c3f489f2ef98f0d5dfac05d3c63339b13802886d53fc05",
+            "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+            "26e8958fc2b227b045c3f489f2ef98f0d5dfac05d3c63339b13802886d53fc85",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "c7176a703d4dd84fba3c0b760d10670f2a2053fa2c39ccc64ec7fd7792ac03fa",
+        ];
+        for (mut point, encoding) in EIGHT_TORSION.into_iter().zip(encodings) {
+            let encoding = bytes_from_str(encoding);
+            assert_eq!(point.encode(), encoding);
+            assert_eq!(ProjEdPoint::decode(encoding).unwrap(), point);
+        }
+    }
+
+    #[test]
+    fn correct_decode_non_canonical() {
+        // y = p + 1 and y = 1 with the sign bit set, both encode the identity
+        let p_plus_1 =
+            bytes_from_str("eeffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f");
+        let minus_zero =
+            bytes_from_str("0100000000000000000000000000000000000000000000000000000000000080");
+        for encoding in [p_plus_1, minus_zero] {
+            assert!(ProjEdPoint::<Curve25519>::decode(encoding).is_err());
+            assert!(ProjEdPoint::<Curve25519>::decode_non_canonical(encoding)
+                .unwrap()
+                .is_identity());
+        }
+
+        // canonical encodings decode to the same point
+        let mut b: ProjEdPoint = ProjEdPoint::basepoint();
+        assert_eq!(ProjEdPoint::decode_non_canonical(b.encode()).unwrap(), b);
+    }
+
+    #[test]
+    fn correct_torsion_checks() {
+        let b: ProjEdPoint = ProjEdPoint::basepoint();
+        assert!(b.is_torsion_free());
+        assert!(!b.is_small_order());
+        assert!(b.mul_by_cofactor().is_torsion_free());
+
+        let mixed = b + &EIGHT_TORSION[3];
+        assert!(!mixed.is_torsion_free());
+        assert!(!mixed.is_small_order());
+        assert!(mixed.mul_by_cofactor().is_torsion_free());
+        assert_eq!(mixed.mul_by_cofactor(), b.mul_by_cofactor());
+    }
+
+    fn equivalent_mul_base(s: Scalar) {
+        assert_eq!(
+            ProjEdPoint::mul_base(&s),
+            ProjEdPoint::basepoint().edwards_mul(&s.to_bytes())
+        );
+    }
+
+    #[test]
+    fn equivalent_mul_base_small() {
+        assert_eq!(
+            ProjEdPoint::mul_base(&Scalar::ZERO) + &ProjEdPoint::basepoint(),
+            ProjEdPoint::basepoint()
+        );
+        equivalent_mul_base(Scalar::ONE);
+        equivalent_mul_base(Scalar::from(8));
+        equivalent_mul_base(-Scalar::ONE);
+    }
+
+    #[test]
+    fn equivalent_mul_base_big() {
+        equivalent_mul_base(Scalar::from_bytes_mod_order_wide(&[0xa5; 64]));
+        equivalent_mul_base(Scalar::from_bytes_mod_order([0xff; 32]));
+    }
+
+    fn equivalent_double_scalar_mul(a: Scalar, b: Scalar) {
+        let a_point = ProjEdPoint::basepoint().edwards_mul(&[7; 32]);
+        let mut expected = a_point.edwards_mul(&a.to_bytes())
+            + &ProjEdPoint::basepoint().edwards_mul(&b.to_bytes());
+        expected.normalize();
+        assert_eq!(
+            ProjEdPoint::vartime_double_scalar_mul_basepoint(&a, &a_point, &b),
+            expected
+        );
+    }
+
+    #[test]
+    fn equivalent_double_scalar_mul_small() {
+        equivalent_double_scalar_mul(Scalar::ONE, Scalar::ONE);
+        equivalent_double_scalar_mul(Scalar::from(15), Scalar::from(127));
+        equivalent_double_scalar_mul(Scalar::ZERO, Scalar::from(128));
+        equivalent_double_scalar_mul(-Scalar::ONE, Scalar::ZERO);
+    }
+
+    #[test]
+    fn equivalent_double_scalar_mul_big() {
+        equivalent_double_scalar_mul(
+            Scalar::from_bytes_mod_order_wide(&[0x5a; 64]),
+            Scalar::from_bytes_mod_order_wide(&[0xa5; 64]),
+        );
+    }
+
     #[test]
     fn equivalent_mul_1() {
-        equivalent_mul(Integer::from(1));
+        equivalent_mul(Scalar::ONE);
     }
 
     #[test]
     fn equivalent_mul_big_1() {
-        equivalent_mul(
-            Integer::from_str_radix(
-                "36144925721603087658594284515452164870581325872720374094707712194495455132720",
-                10,
-            )
-            .unwrap(),
-        );
+        // 36144925721603087658594284515452164870581325872720374094707712194495455132720
+        equivalent_mul(Scalar::from_bytes_mod_order([
+            0x30, 0x7c, 0x83, 0x86, 0x4f, 0x28, 0x33, 0xcb, 0x42, 0x7a, 0x2e, 0xf1, 0xc0, 0x0a,
+            0x01, 0x3c, 0xfd, 0xff, 0x27, 0x68, 0xd9, 0x80, 0xc0, 0xa3, 0xa5, 0x20, 0xf0, 0x06,
+            0x90, 0x4d, 0xe9, 0x4f,
+        ]));
     }
 }
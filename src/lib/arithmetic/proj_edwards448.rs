@@ -0,0 +1,283 @@
+use super::super::Error;
+use super::field448::FieldElement448;
+use super::scalar448::Scalar448;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+// d = -39081
+const D: FieldElement448 = FieldElement448::from_limbs([
+    72_057_594_037_888_854,
+    72_057_594_037_927_935,
+    72_057_594_037_927_935,
+    72_057_594_037_927_935,
+    72_057_594_037_927_934,
+    72_057_594_037_927_935,
+    72_057_594_037_927_935,
+    72_057_594_037_927_935,
+]);
+
+// base point from RFC 8032 (section 5.2)
+const B_X: FieldElement448 = FieldElement448::from_limbs([
+    10_880_955_091_566_686,
+    36_276_784_145_337_894,
+    69_571_282_115_576_635,
+    46_113_124_210_880_026,
+    4_247_859_732_800_292,
+    15_440_021_224_255_559,
+    66_747_077_793_030_847,
+    22_264_495_316_135_181,
+]);
+
+const B_Y: FieldElement448 = FieldElement448::from_limbs([
+    2_385_235_625_966_100,
+    5_396_741_696_826_776,
+    8_134_720_567_442_877,
+    1_584_133_578_609_663,
+    46_047_824_121_994_270,
+    56_121_598_560_924_524,
+    10_283_140_089_599_689,
+    29_624_444_337_960_636,
+]);
+
+/// point of edwards448 (x^2 + y^2 = 1 + d x^2 y^2) in projective coordinates (X : Y : Z), with
+/// x = X / Z and y = Y / Z
+#[derive(Clone, Copy, Debug)]
+pub struct ProjEd448Point {
+    x: FieldElement448,
+    y: FieldElement448,
+    z: FieldElement448,
+}
+
+impl ProjEd448Point {
+    pub const fn new(x: FieldElement448, y: FieldElement448) -> Self {
+        Self {
+            x,
+            y,
+            z: FieldElement448::ONE,
+        }
+    }
+
+    /// neutral element (0, 1)
+    pub const fn identity() -> Self {
+        Self::new(FieldElement448::ZERO, FieldElement448::ONE)
+    }
+
+    /// base point of order L
+    pub const fn basepoint() -> Self {
+        Self::new(B_X, B_Y)
+    }
+
+    pub fn is_identity(&self) -> bool {
+        (self.x.is_zero() & self.y.ct_eq(&self.z)).into()
+    }
+
+    /// doubling formulas from RFC 8032 (section 5.2.4)
+    pub fn double(&self) -> Self {
+        let b = (self.x + self.y).square();
+        let c = self.x.square();
+        let d = self.y.square();
+        let e = c + d;
+        let h = self.z.square();
+        let j = e - (h + h);
+        Self {
+            x: (b - e) * j,
+            y: e * (c - d),
+            z: e * j,
+        }
+    }
+
+    /// compute 4 * self, 4 being the cofactor of edwards448
+    pub fn mul_by_cofactor(&self) -> Self {
+        self.double().double()
+    }
+
+    /// compute self * s in constant time, with a Montgomery ladder on the (complete) Edwards
+    /// formulas which always runs 448 iterations
+    pub fn mul(&self, s: &Scalar448) -> Self {
+        let bytes = s.to_bytes();
+        let mut r0 = Self::identity();
+        let mut r1 = *self;
+        let mut swap = Choice::from(0);
+        for i in (0..448).rev() {
+            let bit = Choice::from((bytes[i / 8] >> (i % 8)) & 1);
+            swap ^= bit;
+            Self::conditional_swap(&mut r0, &mut r1, swap);
+            swap = bit;
+            r1 = r0 + &r1;
+            r0 = r0.double();
+        }
+        Self::conditional_swap(&mut r0, &mut r1, swap);
+        r0
+    }
+
+    pub fn mul_base(s: &Scalar448) -> Self {
+        Self::basepoint().mul(s)
+    }
+
+    /// the 57-byte encoding from RFC 8032 (section 5.2.2): y in little-endian, followed by a byte
+    /// whose most significant bit is the sign of x
+    pub fn encode(&self) -> [u8; 57] {
+        let z_inv = self.z.invert();
+        let x = self.x * z_inv;
+        let y = self.y * z_inv;
+        let mut bytes = [0u8; 57];
+        bytes[..56].copy_from_slice(&y.to_bytes());
+        bytes[56] = x.is_negative().unwrap_u8() << 7;
+        bytes
+    }
+
+    /// fails with `NonCanonicalY`, `NotOnCurve` or `NonCanonicalX`
+    pub fn decode(bytes: &[u8; 57]) -> Result<Self, Error> {
+        let sign = bytes[56] >> 7 == 1;
+        // the other bits of the last byte make y >= p
+        if bytes[56] & 0b0111_1111 != 0 {
+            return Err(Error::NonCanonicalY);
+        }
+        let y_bytes: [u8; 56] = bytes[..56].try_into().unwrap();
+        let y = FieldElement448::from_bytes(&y_bytes);
+
+        // reject y >= p
+        if y.to_bytes() != y_bytes {
+            return Err(Error::NonCanonicalY);
+        }
+
+        // x^2 = (y^2 - 1) / (d y^2 - 1)
+        let y2 = y.square();
+        let (was_square, mut x) = FieldElement448::sqrt_ratio(
+            &(y2 - FieldElement448::ONE),
+            &(D * y2 - FieldElement448::ONE),
+        );
+        if !bool::from(was_square) {
+            return Err(Error::NotOnCurve);
+        }
+        if bool::from(x.is_zero()) && sign {
+            return Err(Error::NonCanonicalX);
+        }
+        if bool::from(x.is_negative()) != sign {
+            x = -x;
+        }
+        Ok(Self::new(x, y))
+    }
+}
+
+impl ConditionallySelectable for ProjEd448Point {
+    /// return b if choice is 1 and a otherwise, without branching on choice
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self {
+            x: FieldElement448::conditional_select(&a.x, &b.x, choice),
+            y: FieldElement448::conditional_select(&a.y, &b.y, choice),
+            z: FieldElement448::conditional_select(&a.z, &b.z, choice),
+        }
+    }
+}
+
+impl ConstantTimeEq for ProjEd448Point {
+    /// compare x / z and y / z without inversion, and without branching on the coordinates
+    fn ct_eq(&self, other: &Self) -> Choice {
+        (self.x * other.z).ct_eq(&(other.x * self.z))
+            & (self.y * other.z).ct_eq(&(other.y * self.z))
+    }
+}
+
+impl PartialEq for ProjEd448Point {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl std::ops::Add<&Self> for ProjEd448Point {
+    type Output = Self;
+
+    /// addition formulas from RFC 8032 (section 5.2.4), complete since d is not a square
+    fn add(self, other: &Self) -> Self {
+        let a = self.z * other.z;
+        let b = a.square();
+        let c = self.x * other.x;
+        let d = self.y * other.y;
+        let e = D * c * d;
+        let f = b - e;
+        let g = b + e;
+        let h = (self.x + self.y) * (other.x + other.y);
+        Self {
+            x: a * f * (h - c - d),
+            y: a * g * (d - c),
+            z: f * g,
+        }
+    }
+}
+
+impl std::ops::Neg for ProjEd448Point {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self { x: -self.x, ..self }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn correct_basepoint() {
+        let b = ProjEd448Point::basepoint();
+        let (x2, y2) = (B_X.square(), B_Y.square());
+        assert_eq!(x2 + y2, FieldElement448::ONE + D * x2 * y2);
+        assert!(ProjEd448Point::decode(&b.encode()).unwrap() == b);
+    }
+
+    #[test]
+    fn correct_double() {
+        let b = ProjEd448Point::basepoint();
+        assert!(b.double() == b + &b);
+        assert!((b + &-b).is_identity());
+        assert!(ProjEd448Point::identity().double().is_identity());
+    }
+
+    #[test]
+    fn correct_mul() {
+        let b = ProjEd448Point::basepoint();
+        let b3 = b.double() + &b;
+        assert!(b.mul(&Scalar448::from(3)) == b3);
+        assert!(ProjEd448Point::mul_base(&Scalar448::ZERO).is_identity());
+    }
+
+    #[test]
+    fn correct_order() {
+        // a B + b B = (a + b mod L) B only if B has order L
+        let a = Scalar448::from_bytes_mod_order(&[0xff; 57]);
+        let b = a * a;
+        let b_point = ProjEd448Point::basepoint();
+        assert!(b_point.mul(&a) + &b_point.mul(&b) == ProjEd448Point::mul_base(&(a + b)));
+    }
+
+    #[test]
+    fn reject_invalid_encodings() {
+        // y = p, and a y for which x does not exist
+        let mut p = [0xff; 57];
+        p[28] = 0xfe;
+        p[56] = 0;
+        assert_eq!(
+            ProjEd448Point::decode(&p).unwrap_err(),
+            Error::NonCanonicalY
+        );
+        let mut y = [0u8; 57];
+        y[0] = 2;
+        assert_eq!(ProjEd448Point::decode(&y).unwrap_err(), Error::NotOnCurve);
+
+        // bits other than the sign in the last byte
+        let mut b = ProjEd448Point::basepoint().encode();
+        b[56] |= 1;
+        assert_eq!(
+            ProjEd448Point::decode(&b).unwrap_err(),
+            Error::NonCanonicalY
+        );
+
+        // x = 0 with the sign bit set
+        let mut one = ProjEd448Point::identity().encode();
+        one[56] |= 0x80;
+        assert_eq!(
+            ProjEd448Point::decode(&one).unwrap_err(),
+            Error::NonCanonicalX
+        );
+    }
+}
@@ -1,45 +1,52 @@
-use super::{montgomery::MPoint, proj_edwards::ProjEdPoint};
-use rug::Integer;
+use super::curve::{Curve25519, CurveParams};
+use super::{field::FieldElement, montgomery::MPoint, proj_edwards::ProjEdPoint};
+use std::marker::PhantomData;
 
-#[derive(Clone)]
-pub struct ProjMPoint {
-    x: Integer,
-    y: Integer,
-    z: Integer,
+/// point of the Montgomery curve of C in projective coordinates (x : y : z)
+#[derive(Clone, Copy)]
+pub struct ProjMPoint<C: CurveParams = Curve25519> {
+    x: FieldElement,
+    y: FieldElement,
+    z: FieldElement,
+    curve: PhantomData<C>,
 }
 
-impl ProjMPoint {
-    pub fn new(x: Integer, y: Integer, z: Integer) -> Self {
-        Self { x, y, z }
+impl<C: CurveParams> ProjMPoint<C> {
+    pub const fn new(x: FieldElement, y: FieldElement, z: FieldElement) -> Self {
+        Self {
+            x,
+            y,
+            z,
+            curve: PhantomData,
+        }
     }
 
-    pub const fn x(&self) -> &Integer {
+    pub const fn x(&self) -> &FieldElement {
         &self.x
     }
 
-    pub const fn y(&self) -> &Integer {
+    pub const fn y(&self) -> &FieldElement {
         &self.y
     }
 
-    pub const fn z(&self) -> &Integer {
+    pub const fn z(&self) -> &FieldElement {
         &self.z
     }
 }
 
-impl From<&MPoint> for ProjMPoint {
-    fn from(p: &MPoint) -> Self {
-        Self::new(p.x().clone(), p.y().clone(), Integer::from(1))
+impl<C: CurveParams> From<&MPoint<C>> for ProjMPoint<C> {
+    fn from(p: &MPoint<C>) -> Self {
+        Self::new(*p.x(), *p.y(), FieldElement::ONE)
     }
 }
 
 // x = (z + y) x, y = (z + y) z, z = (z - y) x
-impl From<&ProjEdPoint> for ProjMPoint {
-    fn from(point: &ProjEdPoint) -> Self {
-        let p = ProjEdPoint::p();
-        let root = ProjEdPoint::root_minus_a_minus_2_mod_p();
-        let x = ((point.z().clone() + point.y()) * point.x()).modulo(&p);
-        let y = (((point.z().clone() + point.y()) * point.z()).modulo(&p) * root).modulo(&p);
-        let z = ((point.z().clone() - point.y()) * point.x()).modulo(&p);
+impl<C: CurveParams> From<&ProjEdPoint<C>> for ProjMPoint<C> {
+    fn from(point: &ProjEdPoint<C>) -> Self {
+        let root = C::ROOT_MINUS_A_MINUS_2;
+        let x = (*point.z() + *point.y()) * *point.x();
+        let y = (*point.z() + *point.y()) * *point.z() * root;
+        let z = (*point.z() - *point.y()) * *point.x();
         Self::new(x, y, z)
     }
 }
@@ -0,0 +1,407 @@
+use super::super::{Error, Key};
+use super::curve::{Curve25519, CurveParams};
+use super::field::FieldElement;
+use super::proj_edwards::ProjEdPoint;
+use super::scalar::Scalar;
+use subtle::ConditionallySelectable;
+
+// constants from RFC 9496, section 4.1
+
+// sqrt(a * d - 1), negative root
+const SQRT_AD_MINUS_ONE: FieldElement = FieldElement::from_limbs([
+    2_241_493_124_984_347,
+    425_987_919_032_274,
+    2_207_028_919_301_688,
+    1_220_490_630_685_848,
+    974_799_131_293_748,
+]);
+
+// 1 / sqrt(a - d)
+const INVSQRT_A_MINUS_D: FieldElement = FieldElement::from_limbs([
+    278_908_739_862_762,
+    821_645_201_101_625,
+    8_113_234_426_968,
+    1_777_959_178_193_151,
+    2_118_520_810_568_447,
+]);
+
+// 1 - d^2
+const ONE_MINUS_D_SQ: FieldElement = FieldElement::from_limbs([
+    1_136_626_929_484_150,
+    1_998_550_399_581_263,
+    496_427_632_559_748,
+    118_527_312_129_759,
+    45_110_755_273_534,
+]);
+
+// (d - 1)^2
+const D_MINUS_ONE_SQ: FieldElement = FieldElement::from_limbs([
+    1_507_062_230_895_904,
+    1_572_317_787_530_805,
+    683_053_064_812_840,
+    317_374_165_784_489,
+    1_572_899_562_415_810,
+]);
+
+/// element of the prime-order group ristretto255 (RFC 9496), represented by any point of its
+/// coset P + E[4] on edwards25519
+#[derive(Clone, Copy, Debug)]
+pub struct RistrettoPoint(ProjEdPoint);
+
+// |x|, the non-negative one of x and -x
+fn abs(x: FieldElement) -> FieldElement {
+    FieldElement::conditional_select(&x, &-x, x.is_negative())
+}
+
+impl RistrettoPoint {
+    pub const fn identity() -> Self {
+        Self(ProjEdPoint::identity())
+    }
+
+    /// the generator of ristretto255, represented by the base point B of edwards25519
+    pub const fn basepoint() -> Self {
+        Self(ProjEdPoint::basepoint())
+    }
+
+    /// one of the edwards25519 points representing self
+    pub const fn as_edwards(&self) -> &ProjEdPoint {
+        &self.0
+    }
+
+    pub fn mul_base(s: &Scalar) -> Self {
+        Self(ProjEdPoint::mul_base(s))
+    }
+
+    pub fn encode(&self) -> Key {
+        let (x0, y0, z0, t0) = (*self.0.x(), *self.0.y(), *self.0.z(), *self.0.t());
+
+        let u1 = (z0 + y0) * (z0 - y0);
+        let u2 = x0 * y0;
+        let (_, invsqrt) = FieldElement::sqrt_ratio_i(&FieldElement::ONE, &(u1 * u2.square()));
+        let den1 = invsqrt * u1;
+        let den2 = invsqrt * u2;
+        let z_inv = den1 * den2 * t0;
+
+        // pick the representative of the coset on which s is computed
+        let rotate = (t0 * z_inv).is_negative();
+        let x = FieldElement::conditional_select(&x0, &(y0 * FieldElement::SQRT_M1), rotate);
+        let y = FieldElement::conditional_select(&y0, &(x0 * FieldElement::SQRT_M1), rotate);
+        let den_inv = FieldElement::conditional_select(&den2, &(den1 * INVSQRT_A_MINUS_D), rotate);
+
+        let y = FieldElement::conditional_select(&y, &-y, (x * z_inv).is_negative());
+        abs(den_inv * (z0 - y)).to_bytes()
+    }
+
+    /// fails with `NonCanonicalY` if s >= p or s is negative, and with `NotOnCurve` if s encodes
+    /// no point
+    pub fn decode(bytes: Key) -> Result<Self, Error> {
+        let s = FieldElement::from_bytes(&bytes);
+
+        // reject s >= p and negative s
+        if s.to_bytes() != bytes || bool::from(s.is_negative()) {
+            return Err(Error::NonCanonicalY);
+        }
+
+        let ss = s.square();
+        let u1 = FieldElement::ONE - ss;
+        let u2 = FieldElement::ONE + ss;
+        let u2_sqr = u2.square();
+        let v = -(Curve25519::D * u1.square()) - u2_sqr;
+
+        let (was_square, invsqrt) = FieldElement::sqrt_ratio_i(&FieldElement::ONE, &(v * u2_sqr));
+        let den_x = invsqrt * u2;
+        let den_y = invsqrt * den_x * v;
+
+        let x = abs((s + s) * den_x);
+        let y = u1 * den_y;
+        let t = x * y;
+
+        if bool::from(!was_square | t.is_negative() | y.is_zero()) {
+            return Err(Error::NotOnCurve);
+        }
+        Ok(Self(ProjEdPoint::from_extended(x, y, FieldElement::ONE, t)))
+    }
+
+    /// one-way map from 64 uniformly random bytes to the group, for hashing to ristretto255
+    pub fn from_uniform_bytes(bytes: &[u8; 64]) -> Self {
+        // from_bytes ignores the most significant bit
+        let t1 = FieldElement::from_bytes(bytes[..32].try_into().unwrap());
+        let t2 = FieldElement::from_bytes(bytes[32..].try_into().unwrap());
+        Self(Self::elligator_map(&t1) + &Self::elligator_map(&t2))
+    }
+
+    // MAP from RFC 9496, section 4.3.4
+    fn elligator_map(t: &FieldElement) -> ProjEdPoint {
+        let d = Curve25519::D;
+        let one = FieldElement::ONE;
+
+        let r = FieldElement::SQRT_M1 * t.square();
+        let u = (r + one) * ONE_MINUS_D_SQ;
+        let v = (-one - r * d) * (r + d);
+
+        let (was_square, s) = FieldElement::sqrt_ratio_i(&u, &v);
+        let s_prime = -abs(s * *t);
+        let s = FieldElement::conditional_select(&s_prime, &s, was_square);
+        let c = FieldElement::conditional_select(&r, &-one, was_square);
+
+        let n = c * (r - one) * D_MINUS_ONE_SQ - v;
+
+        let w0 = (s + s) * v;
+        let w1 = n * SQRT_AD_MINUS_ONE;
+        let w2 = one - s.square();
+        let w3 = one + s.square();
+        ProjEdPoint::from_extended(w0 * w3, w2 * w1, w1 * w3, w0 * w2)
+    }
+}
+
+impl std::ops::Add<&Self> for RistrettoPoint {
+    type Output = Self;
+
+    fn add(self, other: &Self) -> Self {
+        Self(self.0 + &other.0)
+    }
+}
+
+impl std::ops::AddAssign<&Self> for RistrettoPoint {
+    fn add_assign(&mut self, other: &Self) {
+        self.0 += &other.0;
+    }
+}
+
+impl std::ops::Neg for RistrettoPoint {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(-self.0)
+    }
+}
+
+impl std::ops::Sub<&Self> for RistrettoPoint {
+    type Output = Self;
+
+    fn sub(self, other: &Self) -> Self {
+        Self(self.0 - &other.0)
+    }
+}
+
+impl std::iter::Sum for RistrettoPoint {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::identity(), |acc, point| acc + &point)
+    }
+}
+
+impl std::ops::Mul<&Scalar> for RistrettoPoint {
+    type Output = Self;
+
+    fn mul(self, s: &Scalar) -> Self {
+        Self(self.0 * s)
+    }
+}
+
+// two representatives are equal iff they differ by a point of E[4]
+impl PartialEq for RistrettoPoint {
+    fn eq(&self, other: &Self) -> bool {
+        let (x1, y1) = (*self.0.x(), *self.0.y());
+        let (x2, y2) = (*other.0.x(), *other.0.y());
+        x1 * y2 == y1 * x2 || y1 * y2 == x1 * x2
+    }
+}
+
+impl Eq for RistrettoPoint {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lib::arithmetic::proj_edwards::EIGHT_TORSION;
+
+    fn bytes_from_str(s: &str) -> Vec<u8> {
+        s.chars()
+            .collect::<Vec<char>>()
+            .chunks(2)
+            .map(|chunk| chunk.iter().collect::<String>())
+            .map(|byte| u8::from_str_radix(&byte, 16).unwrap())
+            .collect()
+    }
+
+    // RFC 9496, appendix A.1
+    const MULTIPLES_OF_GENERATOR: [&str; 16] = [
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "e2f2ae0a6abc4e71a884a961c500515f58e30b6aa582dd8db6a65945e08d2d76",
+        "6a493210f7499cd17fecb510ae0cea23a110e8d5b901f8acadd3095c73a3b919",
+        "94741f5d5d52755ece4f23f044ee27d5d1ea1e2bd196b462166b16152a9d0259",
+        "da80862773358b466ffadfe0b3293ab3d9fd53c5ea6c955358f568322daf6a57",
+        "e882b131016b52c1d3337080187cf768423efccbb517bb495ab812c4160ff44e",
+        "f64746d3c92b13050ed8d80236a7f0007c3b3f962f5ba793d19a601ebb1df403",
+        "44f53520926ec81fbd5a387845beb7df85a96a24ece18738bdcfa6a7822a176d",
+        "903293d8f2287ebe10e2374dc1a53e0bc887e592699f02d077d5263cdd55601c",
+        "02622ace8f7303a31cafc63f8fc48fdc16e1c8c8d234b2f0d6685282a9076031",
+        "20706fd788b2720a1ed2a5dad4952b01f413bcf0e7564de8cdc816689e2db95f",
+        "bce83f8ba5dd2fa572864c24ba1810f9522bc6004afe95877ac73241cafdab42",
+        "e4549ee16b9aa03099ca208c67adafcafa4c3f3e4e5303de6026e3ca8ff84460",
+        "aa52e000df2e16f55fb1032fc33bc42742dad6bd5a8fc0be0167436c5948501f",
+        "46376b80f409b29dc2b5f6f0c52591990896e5716f41477cd30085ab7f10301e",
+        "e0c418f7c8d9c4cdd7395b93ea124f3ad99021bb681dfc3302a9d99a2e53e64e",
+    ];
+
+    #[test]
+    fn correct_multiples_of_generator() {
+        let mut point = RistrettoPoint::identity();
+        for (i, encoding) in MULTIPLES_OF_GENERATOR.iter().enumerate() {
+            let encoding: Key = bytes_from_str(encoding).try_into().unwrap();
+            assert_eq!(point.encode(), encoding);
+            assert_eq!(RistrettoPoint::decode(encoding).unwrap(), point);
+            assert_eq!(RistrettoPoint::decode(encoding).unwrap().encode(), encoding);
+            assert_eq!(RistrettoPoint::mul_base(&Scalar::from(i as u64)), point);
+            point += &RistrettoPoint::basepoint();
+        }
+    }
+
+    // RFC 9496, appendix A.2
+    #[test]
+    fn reject_bad_encodings() {
+        let bad_encodings = [
+            // non-canonical field encodings
+            "00ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+            "f3ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+            "edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+            // negative field elements
+            "0100000000000000000000000000000000000000000000000000000000000000",
+            "01ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+            "ed57ffd8c914fb201471d1c3d245ce3c746fcbe63a3679d51b6a516ebebe0e20",
+            "c34c4e1826e5d403b78e246e88aa051c36ccf0aafebffe137d148a2bf9104562",
+            "c940e5a4404157cfb1628b108db051a8d439e1a421394ec4ebccb9ec92a8ac78",
+            "47cfc5497c53dc8e61c91d17fd626ffb1c49e2bca94eed052281b510b1117a24",
+            "f1c6165d33367351b0da8f6e4511010c68174a03b6581212c71c0e1d026c3c72",
+            "87260f7a2f12495118360f02c26a470f450dadf34a413d21042b43b9d93e1309",
+            // non-square x^2
+            "26948d35ca62e643e26a83177332e6b6afeb9d08e4268b650f1f5bbd8d81d371",
+            "4eac077a713c57b4f4397629a4145982c661f48044dd3f96427d40b147d9742f",
+            "de6a7b00deadc788eb6b6c8d20c0ae96c2f2019078fa604fee5b87d6e989ad7b",
+            "bcab477be20861e01e4a0e295284146a510150d9817763caf1a6f4b422d67042",
+            "2a292df7e32cababbd9de088d1d1abec9fc0440f637ed2fba145094dc14bea08",
+            "f4a9e534fc0d216c44b218fa0c42d99635a0127ee2e53c712f70609649fdff22",
+            "8268436f8c4126196cf64b3c7ddbda90746a378625f9813dd9b8457077256731",
+            "2810e5cbc2cc4d4eece54f61c6f69758e289aa7ab440b3cbeaa21995c2f4232b",
+            // negative x * y
+            "3eb858e78f5a7254d8c9731174a94f76755fd3941c0ac93735c07ba14579630e",
+            "a45fdc55c76448c049a1ab33f17023edfb2be3581e9c7aade8a6125215e04220",
+            "d483fe813c6ba647ebbfd3ec41adca1c6130c2beeee9d9bf065c8d151c5f396e",
+            "8a2e1d30050198c65a54483123960ccc38aef6848e1ec8f5f780e8523769ba32",
+            "32888462f8b486c68ad7dd9610be5192bbeaf3b443951ac1a8118419d9fa097b",
+            "227142501b9d4355ccba290404bde41575b037693cef1f438c47f8fbf35d1165",
+            "5c37cc491da847cfeb9281d407efc41e15144c876e0170b499a96a22ed31e01e",
+            "445425117cb8c90edcbc7c1cc0e74f747f2c1efa5630a967c64f287792a48a4b",
+            // s = -1, which causes y = 0
+            "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        ];
+        // the first 12 are non-canonical or negative s
+        for (i, encoding) in bad_encodings.into_iter().enumerate() {
+            let encoding: Key = bytes_from_str(encoding).try_into().unwrap();
+            let expected = if i < 12 {
+                Error::NonCanonicalY
+            } else {
+                Error::NotOnCurve
+            };
+            assert_eq!(RistrettoPoint::decode(encoding).unwrap_err(), expected);
+        }
+    }
+
+    // RFC 9496, appendix A.3
+    #[test]
+    fn correct_from_uniform_bytes() {
+        let vectors = [
+            (
+                "5d1be09e3d0c82fc538112490e35701979d99e06ca3e2b5b54bffe8b4dc772c1\
+                 4d98b696a1bbfb5ca32c436cc61c16563790306c79eaca7705668b47dffe5bb6",
+                "3066f82a1a747d45120d1740f14358531a8f04bbffe6a819f86dfe50f44a0a46",
+            ),
+            (
+                "f116b34b8f17ceb56e8732a60d913dd10cce47a6d53bee9204be8b44f6678b27\
+                 0102a56902e2488c46120e9276cfe54638286b9e4b3cdb470b542d46c2068d38",
+                "f26e5b6f7d362d2d2a94c5d0e7602cb4773c95a2e5c31a64f133189fa76ed61b",
+            ),
+            (
+                "8422e1bbdaab52938b81fd602effb6f89110e1e57208ad12d9ad767e2e25510c\
+                 27140775f9337088b982d83d7fcf0b2fa1edffe51952cbe7365e95c86eaf325c",
+                "006ccd2a9e6867e6a2c5cea83d3302cc9de128dd2a9a57dd8ee7b9d7ffe02826",
+            ),
+            (
+                "ac22415129b61427bf464e17baee8db65940c233b98afce8d17c57beeb7876c2\
+                 150d15af1cb1fb824bbd14955f2b57d08d388aab431a391cfc33d5bafb5dbbaf",
+                "f8f0c87cf237953c5890aec3998169005dae3eca1fbb04548c635953c817f92a",
+            ),
+            (
+                "165d697a1ef3d5cf3c38565beefcf88c0f282b8e7dbd28544c483432f1cec767\
+                 5debea8ebb4e5fe7d6f6e5db15f15587ac4d4d4a1de7191e0c1ca6664abcc413",
+                "ae81e7dedf20a497e10c304a765c1767a42d6e06029758d2d7e8ef7cc4c41179",
+            ),
+            (
+                "a836e6c9a9ca9f1e8d486273ad56a78c70cf18f0ce10abb1c7172ddd605d7fd2\
+                 979854f47ae1ccf204a33102095b4200e5befc0465accc263175485f0e17ea5c",
+                "e2705652ff9f5e44d3e841bf1c251cf7dddb77d140870d1ab2ed64f1a9ce8628",
+            ),
+            (
+                "2cdc11eaeb95daf01189417cdddbf95952993aa9cb9c640eb5058d09702c7462\
+                 2c9965a697a3b345ec24ee56335b556e677b30e6f90ac77d781064f866a3c982",
+                "80bd07262511cdde4863f8a7434cef696750681cb9510eea557088f76d9e5065",
+            ),
+            // inputs whose halves are equal modulo p
+            (
+                "edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff\
+                 1200000000000000000000000000000000000000000000000000000000000000",
+                "304282791023b73128d277bdcb5c7746ef2eac08dde9f2983379cb8e5ef0517f",
+            ),
+            (
+                "edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f\
+                 ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "304282791023b73128d277bdcb5c7746ef2eac08dde9f2983379cb8e5ef0517f",
+            ),
+            (
+                "0000000000000000000000000000000000000000000000000000000000000080\
+                 ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+                "304282791023b73128d277bdcb5c7746ef2eac08dde9f2983379cb8e5ef0517f",
+            ),
+            (
+                "0000000000000000000000000000000000000000000000000000000000000000\
+                 1200000000000000000000000000000000000000000000000000000000000080",
+                "304282791023b73128d277bdcb5c7746ef2eac08dde9f2983379cb8e5ef0517f",
+            ),
+        ];
+        for (input, output) in vectors {
+            let input: [u8; 64] = bytes_from_str(input).try_into().unwrap();
+            let output: Key = bytes_from_str(output).try_into().unwrap();
+            assert_eq!(RistrettoPoint::from_uniform_bytes(&input).encode(), output);
+        }
+    }
+
+    #[test]
+    fn correct_coset_equality() {
+        let point = RistrettoPoint::mul_base(&Scalar::from(5));
+        let encoding = point.encode();
+        // E[4] is spanned by EIGHT_TORSION[2]
+        for torsion in [EIGHT_TORSION[2], EIGHT_TORSION[4], EIGHT_TORSION[6]] {
+            let other = RistrettoPoint(*point.as_edwards() + &torsion);
+            assert_eq!(other, point);
+            assert_eq!(other.encode(), encoding);
+        }
+        let other = RistrettoPoint(*point.as_edwards() + &EIGHT_TORSION[1]);
+        assert_ne!(other, point);
+    }
+
+    #[test]
+    fn correct_group_operations() {
+        let a = Scalar::from(1_234_567);
+        let b = Scalar::from(89);
+        let p = RistrettoPoint::mul_base(&a);
+        let q = RistrettoPoint::basepoint() * &b;
+        assert_eq!(p + &q, RistrettoPoint::mul_base(&(a + b)));
+        assert_eq!(p - &q, RistrettoPoint::mul_base(&(a - b)));
+        assert_eq!(-p, RistrettoPoint::mul_base(&-a));
+        assert_eq!(p * &b, q * &a);
+        assert_eq!(
+            [p, q, -p].into_iter().sum::<RistrettoPoint>(),
+            RistrettoPoint::mul_base(&b)
+        );
+    }
+}
@@ -0,0 +1,514 @@
+use zeroize::Zeroize;
+
+const LOW_52_BIT_MASK: u64 = (1 << 52) - 1;
+
+// L = 2^252 + 27742317777372353535851937790883648493, as 5 limbs of 52 bits
+const L: [u64; 5] = [
+    671_914_833_335_277,
+    3_916_664_325_105_025,
+    1_367_801,
+    0,
+    17_592_186_044_416,
+];
+
+// -L^-1 mod 2^52, used by the Montgomery reduction
+const LFACTOR: u64 = 1_439_961_107_955_227;
+
+// R = 2^260 mod L
+const R: [u64; 5] = [
+    4_302_102_966_953_709,
+    1_049_714_374_468_698,
+    4_503_599_278_581_019,
+    4_503_599_627_370_495,
+    17_592_186_044_415,
+];
+
+// RR = 2^520 mod L
+const RR: [u64; 5] = [
+    2_764_609_938_444_603,
+    3_768_881_411_696_287,
+    1_616_719_297_148_420,
+    1_087_343_033_131_391,
+    10_175_238_647_962,
+];
+
+// L - 2, little-endian, exponent used for inversion
+const L_MINUS_2: [u8; 32] = [
+    0xeb, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde, 0x14,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10,
+];
+
+/// integer modulo L = 2^252 + 27742317777372353535851937790883648493, the order of the base point
+///
+/// stored as 5 limbs of 52 bits (little-endian), always fully reduced
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Scalar([u64; 5]);
+
+#[inline(always)]
+fn m(x: u64, y: u64) -> u128 {
+    u128::from(x) * u128::from(y)
+}
+
+impl Scalar {
+    pub const ZERO: Self = Self([0, 0, 0, 0, 0]);
+    pub const ONE: Self = Self([1, 0, 0, 0, 0]);
+
+    // split little-endian bytes into 52-bit limbs, without any reduction
+    fn limbs_from_bytes<const N: usize>(bytes: &[u8]) -> [u64; N] {
+        let mut limbs = [0u64; N];
+        let mut acc: u128 = 0;
+        let mut bits = 0;
+        let mut i = 0;
+        for &byte in bytes {
+            acc |= u128::from(byte) << bits;
+            bits += 8;
+            if bits >= 52 {
+                limbs[i] = (acc as u64) & LOW_52_BIT_MASK;
+                acc >>= 52;
+                bits -= 52;
+                i += 1;
+            }
+        }
+        if i < N {
+            limbs[i] = acc as u64;
+        }
+        limbs
+    }
+
+    /// reduce a 256-bit little-endian integer modulo L
+    pub fn from_bytes_mod_order(bytes: [u8; 32]) -> Self {
+        let x = Self(Self::limbs_from_bytes::<5>(&bytes));
+        // x * R / R = x mod L
+        Self::montgomery_mul(&x, &Self(R))
+    }
+
+    /// reduce a 512-bit little-endian integer (e.g. a SHA-512 digest) modulo L
+    pub fn from_bytes_mod_order_wide(bytes: &[u8; 64]) -> Self {
+        let limbs = Self::limbs_from_bytes::<10>(bytes);
+        let lo = Self([limbs[0], limbs[1], limbs[2], limbs[3], limbs[4]]);
+        let hi = Self([limbs[5], limbs[6], limbs[7], limbs[8], limbs[9]]);
+        // lo + hi * 2^260 mod L
+        Self::montgomery_mul(&lo, &Self(R)) + Self::montgomery_mul(&hi, &Self(RR))
+    }
+
+    /// decode a 256-bit little-endian integer, only if it is already reduced modulo L
+    pub fn from_canonical_bytes(bytes: [u8; 32]) -> Option<Self> {
+        let s = Self::from_bytes_mod_order(bytes);
+        if s.to_bytes() == bytes {
+            Some(s)
+        } else {
+            None
+        }
+    }
+
+    /// canonical little-endian encoding
+    pub fn to_bytes(self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        let mut acc: u128 = 0;
+        let mut bits = 0;
+        let mut i = 0;
+        for limb in self.0 {
+            acc |= u128::from(limb) << bits;
+            bits += 52;
+            while bits >= 8 && i < 32 {
+                bytes[i] = acc as u8;
+                acc >>= 8;
+                bits -= 8;
+                i += 1;
+            }
+        }
+        bytes
+    }
+
+    pub fn is_zero(&self) -> bool {
+        *self == Self::ZERO
+    }
+
+    /// width-w non-adjacent form: digits n_i such that self = sum(n_i * 2^i), where each non-zero
+    /// digit is odd, |n_i| < 2^(w - 1), and any w consecutive digits contain at most one non-zero
+    ///
+    /// the digits depend on the value of self, so this is only for variable-time computations
+    pub(crate) fn non_adjacent_form(&self, w: usize) -> [i8; 256] {
+        assert!((2..=8).contains(&w));
+
+        let bytes = self.to_bytes();
+        let mut x = [0u64; 5];
+        for (word, chunk) in x.iter_mut().zip(bytes.chunks(8)) {
+            *word = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+
+        let width = 1u64 << w;
+        let window_mask = width - 1;
+
+        let mut naf = [0i8; 256];
+        let mut pos = 0;
+        let mut carry = 0;
+        while pos < 256 {
+            let (word, bit) = (pos / 64, pos % 64);
+            let bit_buf = if bit < 64 - w {
+                x[word] >> bit
+            } else {
+                (x[word] >> bit) | (x[word + 1] << (64 - bit))
+            };
+
+            let window = carry + (bit_buf & window_mask);
+            if window & 1 == 0 {
+                pos += 1;
+                continue;
+            }
+
+            if window < width / 2 {
+                carry = 0;
+                naf[pos] = window as i8;
+            } else {
+                carry = 1;
+                naf[pos] = (window as i64 - width as i64) as i8;
+            }
+            pos += w;
+        }
+        naf
+    }
+
+    /// signed radix-16 digits d_i in [-8, 8) such that self = sum(d_i * 16^i)
+    pub(crate) fn to_radix_16(self) -> [i8; 64] {
+        let bytes = self.to_bytes();
+        let mut digits = [0i8; 64];
+        for (i, byte) in bytes.iter().enumerate() {
+            digits[2 * i] = (byte & 15) as i8;
+            digits[2 * i + 1] = (byte >> 4) as i8;
+        }
+
+        // recenter each digit, the last one stays small since self < 2^253
+        for i in 0..63 {
+            let carry = (digits[i] + 8) >> 4;
+            digits[i] -= carry << 4;
+            digits[i + 1] += carry;
+        }
+        digits
+    }
+
+    /// signed digits d_i in radix 2^w, with -2^(w-1) <= d_i < 2^(w-1) except for the last one,
+    /// such that self = sum d_i * 2^(w * i), for 4 <= w <= 8
+    ///
+    /// only the first ceil(256 / w) digits are used
+    pub(crate) fn to_radix_2w(self, w: usize) -> [i8; 64] {
+        assert!((4..=8).contains(&w));
+
+        let bytes = self.to_bytes();
+        let mut x = [0u64; 5];
+        for (word, chunk) in x.iter_mut().zip(bytes.chunks(8)) {
+            *word = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+
+        let radix = 1u64 << w;
+        let window_mask = radix - 1;
+        let digits_count = 256_usize.div_ceil(w);
+
+        let mut digits = [0i8; 64];
+        let mut carry = 0;
+        for (i, digit) in digits.iter_mut().take(digits_count).enumerate() {
+            let (word, bit) = ((i * w) / 64, (i * w) % 64);
+            let bit_buf = if bit < 64 - w {
+                x[word] >> bit
+            } else {
+                (x[word] >> bit) | (x[word + 1] << (64 - bit))
+            };
+
+            let window = carry + (bit_buf & window_mask);
+            // recenter the window, the last one stays small since self < 2^253
+            carry = if i + 1 < digits_count {
+                (window + radix / 2) >> w
+            } else {
+                0
+            };
+            *digit = (window as i64 - (carry << w) as i64) as i8;
+        }
+        digits
+    }
+
+    /// multiplicative inverse using Fermat's little theorem (self^(L - 2)), maps 0 to 0
+    pub fn invert(&self) -> Self {
+        let mut result = Self::ONE;
+        for i in (0..253).rev() {
+            result = result * result;
+            if (L_MINUS_2[i / 8] >> (i % 8)) & 1 == 1 {
+                result = result * *self;
+            }
+        }
+        result
+    }
+
+    // a - b mod L, for a, b < 2^52 * L
+    fn sub_limbs(a: &[u64; 5], b: &[u64; 5]) -> Self {
+        let mut difference = [0u64; 5];
+        let mut borrow: u64 = 0;
+        for i in 0..5 {
+            borrow = a[i].wrapping_sub(b[i] + (borrow >> 63));
+            difference[i] = borrow & LOW_52_BIT_MASK;
+        }
+
+        // add L back if the difference is negative
+        let underflow_mask = ((borrow >> 63) ^ 1).wrapping_sub(1);
+        let mut carry: u64 = 0;
+        for i in 0..5 {
+            carry = (carry >> 52) + difference[i] + (L[i] & underflow_mask);
+            difference[i] = carry & LOW_52_BIT_MASK;
+        }
+        Self(difference)
+    }
+
+    // schoolbook product of the limbs, without any reduction
+    fn mul_internal(a: &Self, b: &Self) -> [u128; 9] {
+        let (a, b) = (a.0, b.0);
+        [
+            m(a[0], b[0]),
+            m(a[0], b[1]) + m(a[1], b[0]),
+            m(a[0], b[2]) + m(a[1], b[1]) + m(a[2], b[0]),
+            m(a[0], b[3]) + m(a[1], b[2]) + m(a[2], b[1]) + m(a[3], b[0]),
+            m(a[0], b[4]) + m(a[1], b[3]) + m(a[2], b[2]) + m(a[3], b[1]) + m(a[4], b[0]),
+            m(a[1], b[4]) + m(a[2], b[3]) + m(a[3], b[2]) + m(a[4], b[1]),
+            m(a[2], b[4]) + m(a[3], b[3]) + m(a[4], b[2]),
+            m(a[3], b[4]) + m(a[4], b[3]),
+            m(a[4], b[4]),
+        ]
+    }
+
+    // compute limbs / 2^260 mod L, for limbs < 2^260 * L
+    fn montgomery_reduce(limbs: &[u128; 9]) -> Self {
+        // add the multiple of L that clears the low 52 bits
+        fn part1(sum: u128) -> (u128, u64) {
+            let p = (sum as u64).wrapping_mul(LFACTOR) & LOW_52_BIT_MASK;
+            ((sum + m(p, L[0])) >> 52, p)
+        }
+
+        fn part2(sum: u128) -> (u128, u64) {
+            let w = (sum as u64) & LOW_52_BIT_MASK;
+            (sum >> 52, w)
+        }
+
+        // L[3] = 0, so its products are skipped
+        let (carry, n0) = part1(limbs[0]);
+        let (carry, n1) = part1(carry + limbs[1] + m(n0, L[1]));
+        let (carry, n2) = part1(carry + limbs[2] + m(n0, L[2]) + m(n1, L[1]));
+        let (carry, n3) = part1(carry + limbs[3] + m(n1, L[2]) + m(n2, L[1]));
+        let (carry, n4) = part1(carry + limbs[4] + m(n0, L[4]) + m(n2, L[2]) + m(n3, L[1]));
+
+        let (carry, r0) = part2(carry + limbs[5] + m(n1, L[4]) + m(n3, L[2]) + m(n4, L[1]));
+        let (carry, r1) = part2(carry + limbs[6] + m(n2, L[4]) + m(n4, L[2]));
+        let (carry, r2) = part2(carry + limbs[7] + m(n3, L[4]));
+        let (carry, r3) = part2(carry + limbs[8] + m(n4, L[4]));
+        let r4 = carry as u64;
+
+        // the result is < 2L, subtract L once if needed
+        Self::sub_limbs(&[r0, r1, r2, r3, r4], &L)
+    }
+
+    // a * b / 2^260 mod L
+    fn montgomery_mul(a: &Self, b: &Self) -> Self {
+        Self::montgomery_reduce(&Self::mul_internal(a, b))
+    }
+}
+
+// secret scalars (e.g. the private scalar and the nonce) are wiped with `Zeroizing<Scalar>`
+impl Zeroize for Scalar {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl From<u64> for Scalar {
+    fn from(n: u64) -> Self {
+        let mut bytes = [0u8; 32];
+        bytes[..8].copy_from_slice(&n.to_le_bytes());
+        Self::from_bytes_mod_order(bytes)
+    }
+}
+
+impl std::ops::Add for Scalar {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let mut sum = [0u64; 5];
+        let mut carry: u64 = 0;
+        for (i, limb) in sum.iter_mut().enumerate() {
+            carry = self.0[i] + rhs.0[i] + (carry >> 52);
+            *limb = carry & LOW_52_BIT_MASK;
+        }
+        Self::sub_limbs(&sum, &L)
+    }
+}
+
+impl std::ops::Sub for Scalar {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::sub_limbs(&self.0, &rhs.0)
+    }
+}
+
+impl std::ops::Neg for Scalar {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::ZERO - self
+    }
+}
+
+impl std::ops::Mul for Scalar {
+    type Output = Self;
+
+    // (a * b / R) * RR / R = a * b mod L
+    fn mul(self, rhs: Self) -> Self {
+        Self::montgomery_mul(&Self::montgomery_mul(&self, &rhs), &Self(RR))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn bytes_from_str(s: &str) -> [u8; 32] {
+        s.chars()
+            .collect::<Vec<char>>()
+            .chunks(2)
+            .map(|chunk| chunk.iter().collect::<String>())
+            .map(|byte| u8::from_str_radix(&byte, 16).unwrap())
+            .collect::<Vec<u8>>()
+            .try_into()
+            .unwrap()
+    }
+
+    fn a() -> Scalar {
+        Scalar::from_canonical_bytes(bytes_from_str(
+            "1c5b0b2dfd8d6a43ff2b7e39d0e3e9b6e6a2a36a3dfeb18c3c1d1a4b8e5f6a07",
+        ))
+        .unwrap()
+    }
+
+    fn b() -> Scalar {
+        Scalar::from_canonical_bytes(bytes_from_str(
+            "f0e1d2c3b4a5968778695a4b3c2d1e0f00112233445566778899aabbccddee0e",
+        ))
+        .unwrap()
+    }
+
+    fn correct_op(result: Scalar, expected: &str) {
+        assert_eq!(result.to_bytes(), bytes_from_str(expected));
+    }
+
+    fn correct_naf(s: Scalar, w: usize) {
+        let naf = s.non_adjacent_form(w);
+        let mut sum = Scalar::ZERO;
+        let mut power = Scalar::ONE;
+        for (i, &digit) in naf.iter().enumerate() {
+            if digit != 0 {
+                assert_eq!(digit.abs() % 2, 1);
+                assert!(i32::from(digit.abs()) < 1 << (w - 1));
+                assert!(naf[i + 1..(i + w).min(256)].iter().all(|&d| d == 0));
+            }
+            let abs = Scalar::from(u64::from(digit.unsigned_abs()));
+            let signed = if digit < 0 { -abs } else { abs };
+            sum = sum + signed * power;
+            power = power + power;
+        }
+        assert_eq!(sum, s);
+    }
+
+    #[test]
+    fn correct_non_adjacent_form() {
+        for w in [5, 8] {
+            correct_naf(a(), w);
+            correct_naf(b(), w);
+            correct_naf(-Scalar::ONE, w);
+        }
+    }
+
+    fn correct_radix_2w(s: Scalar, w: usize) {
+        let digits = s.to_radix_2w(w);
+        let digits_count = 256_usize.div_ceil(w);
+        let radix = Scalar::from(1 << w);
+        let mut sum = Scalar::ZERO;
+        for (i, &digit) in digits.iter().enumerate().rev() {
+            if i >= digits_count {
+                assert_eq!(digit, 0);
+                continue;
+            }
+            if i + 1 < digits_count {
+                assert!((-(1 << (w - 1))..(1 << (w - 1))).contains(&i32::from(digit)));
+            }
+            let abs = Scalar::from(u64::from(digit.unsigned_abs()));
+            let signed = if digit < 0 { -abs } else { abs };
+            sum = sum * radix + signed;
+        }
+        assert_eq!(sum, s);
+    }
+
+    #[test]
+    fn correct_radix_2w_digits() {
+        for w in 4..=8 {
+            correct_radix_2w(a(), w);
+            correct_radix_2w(b(), w);
+            correct_radix_2w(-Scalar::ONE, w);
+            correct_radix_2w(Scalar::ZERO, w);
+        }
+    }
+
+    #[test]
+    fn correct_canonical_bytes() {
+        let mut l = L_MINUS_2;
+        l[0] += 2;
+        assert!(Scalar::from_canonical_bytes(l).is_none());
+        l[0] -= 1;
+        assert_eq!(Scalar::from_canonical_bytes(l).unwrap(), -Scalar::ONE);
+    }
+
+    #[test]
+    fn correct_reduction() {
+        correct_op(
+            Scalar::from_bytes_mod_order([0xff; 32]),
+            "1c95988d7431ecd670cf7d73f45befc6feffffffffffffffffffffffffffff0f",
+        );
+    }
+
+    #[test]
+    fn correct_wide_reduction() {
+        correct_op(
+            Scalar::from_bytes_mod_order_wide(&[0xff; 64]),
+            "000f9c44e31106a447938568a71b0ed065bef517d273ecce3d9a307c1b419903",
+        );
+    }
+
+    #[test]
+    fn correct_add() {
+        correct_op(
+            a() + b(),
+            "1f69e89397d0ee72a1f8e0e12d1729b1e6b3c59d81531804c5b6c4065b3d5906",
+        );
+    }
+
+    #[test]
+    fn correct_sub() {
+        correct_op(
+            a() - b(),
+            "194d2ec6624be6135d5f1b9172b0aabce6918137f9a84b15b4836f8fc1817b08",
+        );
+        assert_eq!(a() + (-a()), Scalar::ZERO);
+    }
+
+    #[test]
+    fn correct_mul() {
+        correct_op(
+            a() * b(),
+            "fb4cd11a18d209a3cb9e4be4286a3bb6dd1d4424db939786c8bbfaffab238c07",
+        );
+    }
+
+    #[test]
+    fn correct_invert() {
+        correct_op(
+            a().invert(),
+            "03762266d98d43670c9e88191c84bf94d43b97f0d07fb7a5c780a12f6525ed04",
+        );
+        assert_eq!(a() * a().invert(), Scalar::ONE);
+    }
+}
@@ -0,0 +1,278 @@
+use zeroize::Zeroize;
+
+const LOW_56_BIT_MASK: u64 = (1 << 56) - 1;
+
+// L = 2^446 - 13818066809895115352007386748515426880336692474882178609894547503885, as 8 limbs
+// of 56 bits
+const L: [u64; 8] = [
+    33_990_932_400_981_235,
+    54_732_098_712_327_459,
+    20_739_908_361_527_660,
+    35_125_152_706_744_027,
+    72_057_594_037_927_935,
+    72_057_594_037_927_935,
+    72_057_594_037_927_935,
+    18_014_398_509_481_983,
+];
+
+// -L^-1 mod 2^56, used by the Montgomery reduction
+const LFACTOR: u64 = 53_273_604_742_286_277;
+
+// R = 2^448 mod L
+const R: [u64; 8] = [
+    8_151_458_471_930_932,
+    69_301_981_302_401_906,
+    61_155_554_629_745_228,
+    3_614_577_248_879_762,
+    2,
+    0,
+    0,
+    0,
+];
+
+// RR = 2^896 mod L
+const RR: [u64; 8] = [
+    23_523_325_504_101_216,
+    12_468_194_255_428_067,
+    10_002_163_197_115_123,
+    26_710_265_115_272_926,
+    55_305_729_855_704_946,
+    31_717_726_225_517_475,
+    11_576_013_770_712_272,
+    14_639_624_634_246_071,
+];
+
+// RRR = 2^1344 mod L
+const RRR: [u64; 8] = [
+    61_777_033_073_226_989,
+    14_452_321_779_332_706,
+    39_230_193_858_261_717,
+    38_155_238_988_713_228,
+    23_846_492_045_797_869,
+    32_490_451_682_130_023,
+    68_096_495_621_254_059,
+    3_712_659_767_507_701,
+];
+
+/// integer modulo L = 2^446 - 13818066809895115352007386748515426880336692474882178609894547503885,
+/// the order of the base point of edwards448
+///
+/// stored as 8 limbs of 56 bits (little-endian), always fully reduced
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Scalar448([u64; 8]);
+
+#[inline(always)]
+fn m(x: u64, y: u64) -> u128 {
+    u128::from(x) * u128::from(y)
+}
+
+impl Scalar448 {
+    pub const ZERO: Self = Self([0, 0, 0, 0, 0, 0, 0, 0]);
+    pub const ONE: Self = Self([1, 0, 0, 0, 0, 0, 0, 0]);
+
+    // split at most 56 little-endian bytes into 56-bit limbs, without any reduction
+    fn limbs_from_bytes(bytes: &[u8]) -> [u64; 8] {
+        let mut limbs = [0u64; 8];
+        for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks(7)) {
+            let mut word = [0u8; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            *limb = u64::from_le_bytes(word);
+        }
+        limbs
+    }
+
+    /// reduce a 912-bit little-endian integer (e.g. a SHAKE256 output) modulo L
+    pub fn from_bytes_mod_order_wide(bytes: &[u8; 114]) -> Self {
+        let lo = Self(Self::limbs_from_bytes(&bytes[..56]));
+        let mid = Self(Self::limbs_from_bytes(&bytes[56..112]));
+        let hi = Self(Self::limbs_from_bytes(&bytes[112..]));
+        // lo + mid * 2^448 + hi * 2^896 mod L
+        Self::montgomery_mul(&lo, &Self(R))
+            + Self::montgomery_mul(&mid, &Self(RR))
+            + Self::montgomery_mul(&hi, &Self(RRR))
+    }
+
+    /// reduce a 456-bit little-endian integer modulo L
+    pub fn from_bytes_mod_order(bytes: &[u8; 57]) -> Self {
+        let mut wide = [0u8; 114];
+        wide[..57].copy_from_slice(bytes);
+        let s = Self::from_bytes_mod_order_wide(&wide);
+        wide.zeroize();
+        s
+    }
+
+    /// decode a 456-bit little-endian integer, only if it is already reduced modulo L
+    pub fn from_canonical_bytes(bytes: &[u8; 57]) -> Option<Self> {
+        let s = Self::from_bytes_mod_order(bytes);
+        if s.to_bytes() == *bytes {
+            Some(s)
+        } else {
+            None
+        }
+    }
+
+    /// canonical little-endian encoding, the last byte is always 0
+    pub fn to_bytes(self) -> [u8; 57] {
+        let mut bytes = [0u8; 57];
+        for (chunk, limb) in bytes.chunks_mut(7).zip(self.0) {
+            chunk.copy_from_slice(&limb.to_le_bytes()[..7]);
+        }
+        bytes
+    }
+
+    // a - b mod L, for a, b < 2^56 * L
+    fn sub_limbs(a: &[u64; 8], b: &[u64; 8]) -> Self {
+        let mut difference = [0u64; 8];
+        let mut borrow: u64 = 0;
+        for i in 0..8 {
+            borrow = a[i].wrapping_sub(b[i] + (borrow >> 63));
+            difference[i] = borrow & LOW_56_BIT_MASK;
+        }
+
+        // add L back if the difference is negative
+        let underflow_mask = ((borrow >> 63) ^ 1).wrapping_sub(1);
+        let mut carry: u64 = 0;
+        for i in 0..8 {
+            carry = (carry >> 56) + difference[i] + (L[i] & underflow_mask);
+            difference[i] = carry & LOW_56_BIT_MASK;
+        }
+        Self(difference)
+    }
+
+    // schoolbook product of the limbs, without any reduction
+    fn mul_internal(a: &Self, b: &Self) -> [u128; 15] {
+        let mut c = [0u128; 15];
+        for i in 0..8 {
+            for j in 0..8 {
+                c[i + j] += m(a.0[i], b.0[j]);
+            }
+        }
+        c
+    }
+
+    // compute limbs / 2^448 mod L, for limbs < 2^448 * L
+    fn montgomery_reduce(limbs: &[u128; 15]) -> Self {
+        // add the multiples of L that clear the low 56 bits, one limb at a time
+        let mut n = [0u64; 8];
+        let mut carry: u128 = 0;
+        for i in 0..8 {
+            let mut sum = carry + limbs[i];
+            for j in 0..i {
+                sum += m(n[j], L[i - j]);
+            }
+            n[i] = (sum as u64).wrapping_mul(LFACTOR) & LOW_56_BIT_MASK;
+            carry = (sum + m(n[i], L[0])) >> 56;
+        }
+
+        let mut r = [0u64; 8];
+        for i in 8..15 {
+            let mut sum = carry + limbs[i];
+            for j in (i - 7)..8 {
+                sum += m(n[j], L[i - j]);
+            }
+            r[i - 8] = (sum as u64) & LOW_56_BIT_MASK;
+            carry = sum >> 56;
+        }
+        r[7] = carry as u64;
+
+        // the result is < 2L, subtract L once if needed
+        Self::sub_limbs(&r, &L)
+    }
+
+    // a * b / 2^448 mod L
+    fn montgomery_mul(a: &Self, b: &Self) -> Self {
+        Self::montgomery_reduce(&Self::mul_internal(a, b))
+    }
+}
+
+// secret scalars (e.g. the private scalar and the nonce) are wiped with `Zeroizing<Scalar448>`
+impl Zeroize for Scalar448 {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl From<u64> for Scalar448 {
+    fn from(n: u64) -> Self {
+        let mut bytes = [0u8; 57];
+        bytes[..8].copy_from_slice(&n.to_le_bytes());
+        Self::from_bytes_mod_order(&bytes)
+    }
+}
+
+impl std::ops::Add for Scalar448 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let mut sum = [0u64; 8];
+        let mut carry: u64 = 0;
+        for (i, limb) in sum.iter_mut().enumerate() {
+            carry = self.0[i] + rhs.0[i] + (carry >> 56);
+            *limb = carry & LOW_56_BIT_MASK;
+        }
+        // self + rhs < 2L, subtract L once if needed
+        Self::sub_limbs(&sum, &L)
+    }
+}
+
+impl std::ops::Mul for Scalar448 {
+    type Output = Self;
+
+    // (a * b / R) * R^2 / R = a * b mod L
+    fn mul(self, rhs: Self) -> Self {
+        let ab = Self::montgomery_mul(&self, &rhs);
+        Self::montgomery_mul(&ab, &Self(RR))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // L - 1, little-endian
+    fn l_minus_one() -> [u8; 57] {
+        let mut bytes = Scalar448(L).to_bytes();
+        bytes[0] -= 1;
+        bytes
+    }
+
+    #[test]
+    fn correct_bytes_round_trip() {
+        let bytes = l_minus_one();
+        assert_eq!(
+            Scalar448::from_canonical_bytes(&bytes).unwrap().to_bytes(),
+            bytes
+        );
+    }
+
+    #[test]
+    fn reject_non_canonical() {
+        assert_eq!(
+            Scalar448::from_canonical_bytes(&Scalar448(L).to_bytes()),
+            None
+        );
+        assert_eq!(Scalar448::from_canonical_bytes(&[0xff; 57]), None);
+    }
+
+    #[test]
+    fn correct_wrap_around() {
+        let minus_one = Scalar448::from_canonical_bytes(&l_minus_one()).unwrap();
+        assert_eq!(minus_one + Scalar448::ONE, Scalar448::ZERO);
+        assert_eq!(minus_one * minus_one, Scalar448::ONE);
+        assert_eq!(Scalar448::from(6) * Scalar448::from(7), Scalar448::from(42));
+    }
+
+    #[test]
+    fn correct_wide_reduction() {
+        // 2^912 - 1 mod L, computed with Python
+        let expected = Scalar448::from_canonical_bytes(&[
+            0x81, 0xde, 0xe7, 0x31, 0xa9, 0x3f, 0x88, 0x11, 0x2e, 0x1d, 0xad, 0x87, 0x07, 0x16,
+            0x0f, 0x80, 0x29, 0x3e, 0xa6, 0x37, 0xfb, 0x19, 0xe3, 0x20, 0xc5, 0xb6, 0x24, 0xbb,
+            0x85, 0xc9, 0x72, 0xcf, 0x17, 0xae, 0x44, 0x7c, 0xc4, 0xa3, 0x4b, 0xc1, 0x9c, 0x1a,
+            0xaf, 0x70, 0xd0, 0xe4, 0xb7, 0xbc, 0x52, 0x20, 0x29, 0xb7, 0x23, 0xf8, 0x39, 0x29,
+            0x00,
+        ])
+        .unwrap();
+        assert_eq!(Scalar448::from_bytes_mod_order_wide(&[0xff; 114]), expected);
+    }
+}
@@ -1,264 +1,279 @@
-use rug::Integer;
+use super::curve::CurveParams;
+use super::field448::FieldElement448;
+use super::{
+    field::FieldElement, montgomery::MPoint, proj_edwards::ProjEdPoint, proj_montgomery::ProjMPoint,
+};
+use std::ops::{Add, Mul, Sub};
+use subtle::{Choice, ConditionallySelectable};
 
-use super::{montgomery::MPoint, proj_edwards::ProjEdPoint, proj_montgomery::ProjMPoint};
-
-#[derive(Clone)]
-pub struct XLineProjMPoint {
-    x: Integer,
-    z: Integer,
+/// field operations used by the x-only Montgomery ladder, so that it runs over GF(2^255 - 19)
+/// (X25519) and GF(2^448 - 2^224 - 1) (X448)
+pub(crate) trait LadderField:
+    Copy + ConditionallySelectable + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+    fn square(&self) -> Self;
+    fn invert(&self) -> Self;
 }
 
-impl std::ops::Mul<bool> for XLineProjMPoint {
-    type Output = Self;
-    /// constant time multiplication of a `XLineProjMPoint` by a bool
-    fn mul(self, rhs: bool) -> Self {
-        let f = u32::from(rhs);
-        let zero = Self {
-            x: Integer::from(0),
-            z: Integer::from(0),
-        };
-        Self {
-            x: self.x * f + zero.x * (1 - f),
-            z: self.z * f + zero.z * (1 - f),
-        }
+impl LadderField for FieldElement {
+    const ZERO: Self = FieldElement::ZERO;
+    const ONE: Self = FieldElement::ONE;
+
+    fn square(&self) -> Self {
+        FieldElement::square(self)
     }
-}
 
-impl std::ops::Add<Self> for XLineProjMPoint {
-    type Output = Self;
-    /// coordinate-wise addition of two `XLineProjMPoint`
-    fn add(self, rhs: Self) -> Self {
-        Self {
-            x: self.x + rhs.x,
-            z: self.z + rhs.z,
-        }
+    fn invert(&self) -> Self {
+        FieldElement::invert(self)
     }
 }
 
-impl From<ProjEdPoint> for XLineProjMPoint {
-    /// convert an `EdPoint` to a `XLineProjMPoint`
-    fn from(point: ProjEdPoint) -> Self {
-        Self {
-            x: point.z().clone() + point.y(),
-            z: point.z().clone() - point.y(),
-        }
+impl LadderField for FieldElement448 {
+    const ZERO: Self = FieldElement448::ZERO;
+    const ONE: Self = FieldElement448::ONE;
+
+    fn square(&self) -> Self {
+        FieldElement448::square(self)
     }
-}
 
-impl XLineProjMPoint {
-    pub const fn x(&self) -> &Integer {
-        &self.x
+    fn invert(&self) -> Self {
+        FieldElement448::invert(self)
     }
+}
 
-    pub const fn z(&self) -> &Integer {
-        &self.z
+#[derive(Clone, Copy)]
+pub struct XLineProjMPoint<F: LadderField = FieldElement> {
+    x: F,
+    z: F,
+}
+
+impl<F: LadderField> ConditionallySelectable for XLineProjMPoint<F> {
+    /// return b if choice is 1 and a otherwise, without branching on choice
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self {
+            x: F::conditional_select(&a.x, &b.x, choice),
+            z: F::conditional_select(&a.z, &b.z, choice),
+        }
     }
+}
 
-    /// coordinate-wise modulo of a `XLineProjMPoint`
-    fn modulo(self, p: &Integer) -> Self {
+impl<C: CurveParams> From<ProjEdPoint<C>> for XLineProjMPoint {
+    /// convert an `EdPoint` to a `XLineProjMPoint`
+    fn from(point: ProjEdPoint<C>) -> Self {
         Self {
-            x: self.x.modulo(p),
-            z: self.z.modulo(p),
+            x: *point.z() + *point.y(),
+            z: *point.z() - *point.y(),
         }
     }
+}
 
+impl<F: LadderField> XLineProjMPoint<F> {
     /// replace x by x/z and z by 1
-    fn normalize(self, p: &Integer) -> Self {
+    fn normalize(self) -> Self {
         Self {
-            x: (self.x * self.z.invert(p).unwrap()).modulo(p),
-            z: Integer::from(1),
+            x: self.x * self.z.invert(),
+            z: F::ONE,
         }
     }
 }
 
 // pseudo-add for Montgomery ladder
-fn x_add(
-    p: &Integer,
-    x_p: &XLineProjMPoint,
-    x_q: &XLineProjMPoint,
-    x_pmq: &XLineProjMPoint,
-) -> XLineProjMPoint {
-    let two = Integer::from(2);
-    let u = ((x_p.x.clone() - &x_p.z).modulo(p) * (x_q.x.clone() + &x_q.z).modulo(p)).modulo(p);
-    let v = ((x_p.x.clone() + &x_p.z).modulo(p) * (x_q.x.clone() - &x_q.z).modulo(p)).modulo(p);
-    let x = x_pmq.z.clone() * ((u.clone() + &v).secure_pow_mod(&two, p));
-    let z = x_pmq.x.clone() * ((u - v).secure_pow_mod(&two, p));
+fn x_add<F: LadderField>(
+    x_p: &XLineProjMPoint<F>,
+    x_q: &XLineProjMPoint<F>,
+    x_pmq: &XLineProjMPoint<F>,
+) -> XLineProjMPoint<F> {
+    let u = (x_p.x - x_p.z) * (x_q.x + x_q.z);
+    let v = (x_p.x + x_p.z) * (x_q.x - x_q.z);
+    let x = x_pmq.z * (u + v).square();
+    let z = x_pmq.x * (u - v).square();
     XLineProjMPoint { x, z }
 }
 
-// pseudo-double for Montgomery ladder
-fn x_dbl(p: &Integer, a: &Integer, x_p: &XLineProjMPoint) -> XLineProjMPoint {
-    let two = Integer::from(2);
-    let q = (x_p.x.clone() + &x_p.z).secure_pow_mod(&two, p);
-    let r = (x_p.x.clone() - &x_p.z).secure_pow_mod(&two, p);
-    let s = (q.clone() - &r).modulo(p);
-    let x = (q * &r).modulo(p);
-    let ap2o4ts = ((((a.clone() + 2) * (Integer::from(4).invert(p).unwrap()).modulo(p)) * &s)
-        as Integer)
-        .modulo(p);
-    let z: Integer = (((r + ap2o4ts) * s) as Integer).modulo(p);
+// pseudo-double for Montgomery ladder, a24 = (a + 2) / 4
+fn x_dbl<F: LadderField>(a24: &F, x_p: &XLineProjMPoint<F>) -> XLineProjMPoint<F> {
+    let q = (x_p.x + x_p.z).square();
+    let r = (x_p.x - x_p.z).square();
+    let s = q - r;
+    let x = q * r;
+    let z = (r + *a24 * s) * s;
     XLineProjMPoint { x, z }
 }
 
-// Montgomery ladder for constant time scalar multiplication
-fn ladder(p: &Integer, a: &Integer, m: &Integer, x_p: &Integer) -> (Integer, Integer) {
-    let u = XLineProjMPoint {
-        x: x_p.clone(),
-        z: Integer::from(1),
-    };
-    let mut x_0 = XLineProjMPoint {
-        x: Integer::from(1),
-        z: Integer::from(0),
-    };
-    let mut x_1 = u.clone();
-    for i in (0..m.significant_bits()).rev() {
-        let add = x_add(p, &x_0, &x_1, &u);
-        let dbl_0 = x_dbl(p, a, &x_0);
-        let dbl_1 = x_dbl(p, a, &x_1);
-        let bit = m.get_bit(i);
-        x_0 = (add.clone() * bit + dbl_0 * !bit).modulo(p);
-        x_1 = (dbl_1 * bit + add * !bit).modulo(p);
-    }
-    (x_0.normalize(p).x, x_1.normalize(p).x)
-}
-
-// Okeya–Sakurai y-coordinate recovery, returns point q
-fn y_recovery(
-    p: &Integer, // p = 2^255 - 19 for curve25519
-    a: &Integer, // a = 486662 for curve25519
-    b: &Integer, // b = 1 for curve25519
-    point: &MPoint,
-    x_q: &XLineProjMPoint,
-    x_plus: &XLineProjMPoint,
-) -> ProjMPoint {
-    let two = Integer::from(2);
-    let mut v1 = (point.x().clone() * x_q.z()).modulo(p);
-    let mut v2 = (x_q.x.clone() + &v1).modulo(p);
-    let v3 = ((x_q.x.clone() - &v1).secure_pow_mod(&two, p) * x_plus.x()).modulo(p);
-    v1 = (Integer::from(2) * a * x_q.z()).modulo(p);
-    v2 = ((v2 + &v1) * (point.x().clone() * x_q.x() + x_q.z()).modulo(p)).modulo(p);
-    v1 = (v1 * x_q.z()).modulo(p);
-    v2 = ((v2 - &v1) * x_plus.z()).modulo(p);
-    let y = (v2 - &v3).modulo(p);
-    v1 = Integer::from(2) * b * point.y() * x_q.z() * x_plus.z();
-    let x = (v1.clone() * x_q.x()).modulo(p);
-    let z = (v1 * x_q.z()).modulo(p);
-    ProjMPoint::new(x, y, z)
+// Montgomery ladder for constant time scalar multiplication on the Montgomery curve of C, m is
+// little-endian
+//
+// as in RFC 7748 (section 5), the loop always runs over bits 254 to 0 and bit 255 of m is
+// ignored: it is 0 for clamped scalars and for scalars reduced modulo L
+// returns the x-coordinates of m * P and (m + 1) * P
+pub(crate) fn ladder<C: CurveParams>(
+    m: &[u8; 32],
+    x_p: &FieldElement,
+) -> (FieldElement, FieldElement) {
+    let a24 = (C::A + FieldElement::from(2)) * FieldElement::from(4).invert();
+    ladder_bits(&a24, m, 255, x_p)
 }
 
-// combines ladder and y_recovery to compute scalar multiplication on full montgomery points
-pub fn scalar_mul(
-    p: &Integer,
-    a: &Integer,
-    b: &Integer,
-    m: &Integer,
-    point: &MPoint,
-) -> ProjMPoint {
-    let (x_0, x_1) = ladder(p, a, m, point.x());
-    let x_0_point = XLineProjMPoint {
-        x: x_0,
-        z: Integer::from(1),
-    };
-    let x_1_point = XLineProjMPoint {
-        x: x_1,
-        z: Integer::from(1),
+// the ladder over any field, on the curve with (A + 2) / 4 = a24, which runs over bits
+// bits - 1 to 0 of the little-endian m
+pub(crate) fn ladder_bits<F: LadderField>(a24: &F, m: &[u8], bits: usize, x_p: &F) -> (F, F) {
+    let u = XLineProjMPoint { x: *x_p, z: F::ONE };
+    let mut x_0 = XLineProjMPoint {
+        x: F::ONE,
+        z: F::ZERO,
     };
-    y_recovery(p, a, b, point, &x_0_point, &x_1_point)
+    let mut x_1 = u;
+    let mut swap = Choice::from(0);
+    for i in (0..bits).rev() {
+        let bit = Choice::from((m[i / 8] >> (i % 8)) & 1);
+        swap ^= bit;
+        XLineProjMPoint::conditional_swap(&mut x_0, &mut x_1, swap);
+        swap = bit;
+        x_1 = x_add(&x_0, &x_1, &u);
+        x_0 = x_dbl(a24, &x_0);
+    }
+    XLineProjMPoint::conditional_swap(&mut x_0, &mut x_1, swap);
+    (x_0.normalize().x, x_1.normalize().x)
 }
 
-impl From<&ProjMPoint> for XLineProjMPoint {
-    fn from(p: &ProjMPoint) -> Self {
+impl<C: CurveParams> From<&ProjMPoint<C>> for XLineProjMPoint {
+    fn from(p: &ProjMPoint<C>) -> Self {
         Self {
-            x: p.x().clone(),
-            z: p.z().clone(),
+            x: *p.x(),
+            z: *p.z(),
         }
     }
 }
 
-impl From<&MPoint> for XLineProjMPoint {
-    fn from(p: &MPoint) -> Self {
+impl<C: CurveParams> From<&MPoint<C>> for XLineProjMPoint {
+    fn from(p: &MPoint<C>) -> Self {
         Self {
-            x: p.x().clone(),
-            z: Integer::from(1),
+            x: *p.x(),
+            z: FieldElement::ONE,
         }
     }
 }
 
-impl From<&ProjEdPoint> for XLineProjMPoint {
-    fn from(p: &ProjEdPoint) -> Self {
+impl<C: CurveParams> From<&ProjEdPoint<C>> for XLineProjMPoint {
+    fn from(p: &ProjEdPoint<C>) -> Self {
         Self::from(&ProjMPoint::from(p))
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::curve::Curve25519;
     use super::*;
 
-    #[test]
-    fn test_x_dbl() {
-        let p = Integer::from(101);
-        let a = Integer::from(49);
-        let x_p = XLineProjMPoint {
-            x: Integer::from(2),
-            z: Integer::from(1),
-        };
-        let dbl = x_dbl(&p, &a, &x_p);
-        assert_eq!(dbl.x, Integer::from(9));
-        assert_eq!(dbl.z, Integer::from(16));
+    fn bytes_from_str(s: &str) -> [u8; 32] {
+        s.chars()
+            .collect::<Vec<char>>()
+            .chunks(2)
+            .map(|chunk| chunk.iter().collect::<String>())
+            .map(|byte| u8::from_str_radix(&byte, 16).unwrap())
+            .collect::<Vec<u8>>()
+            .try_into()
+            .unwrap()
     }
 
-    fn test_ladder(p: u32, a: u32, m: u32, x: u32, expected: u32) {
-        let p = Integer::from(p);
-        let a = Integer::from(a);
-        let x = Integer::from(x);
-        let m = Integer::from(m);
-        let expected = Integer::from(expected);
-        let (result, _) = ladder(&p, &a, &m, &x);
-        assert_eq!(result, expected);
+    fn a24() -> FieldElement {
+        FieldElement::from(121_666)
     }
 
     #[test]
-    fn test_ladder_101_2() {
-        test_ladder(101, 49, 2, 2, 70);
+    fn test_x_dbl() {
+        let b: ProjEdPoint = ProjEdPoint::basepoint();
+        let x_b = XLineProjMPoint::from(&b).normalize();
+        let dbl = x_dbl(&a24(), &x_b).normalize();
+        let expected = XLineProjMPoint::from(&(b + &b)).normalize();
+        assert_eq!(dbl.x, expected.x);
     }
 
     #[test]
-    fn test_ladder_101_3() {
-        test_ladder(101, 49, 3, 2, 59);
+    fn test_x_add() {
+        let b: ProjEdPoint = ProjEdPoint::basepoint();
+        let b2 = b + &b;
+        let x_b = XLineProjMPoint::from(&b);
+        let sum = x_add(&XLineProjMPoint::from(&b2), &x_b, &x_b).normalize();
+        let expected = XLineProjMPoint::from(&(b2 + &b)).normalize();
+        assert_eq!(sum.x, expected.x);
     }
 
-    #[test]
-    fn test_ladder_101_77() {
-        test_ladder(101, 49, 77, 2, 8);
+    fn test_ladder_small(m: u8, expected: &ProjEdPoint) {
+        let mut scalar = [0u8; 32];
+        scalar[0] = m;
+        let x_b = XLineProjMPoint::from(&ProjEdPoint::<Curve25519>::basepoint())
+            .normalize()
+            .x;
+        let (result, _) = ladder::<Curve25519>(&scalar, &x_b);
+        assert_eq!(result, XLineProjMPoint::from(expected).normalize().x);
     }
 
     #[test]
-    fn test_ladder_1009_2() {
-        test_ladder(1009, 682, 2, 7, 284);
+    fn test_ladder_2() {
+        let b: ProjEdPoint = ProjEdPoint::basepoint();
+        test_ladder_small(2, &(b + &b));
     }
 
     #[test]
-    fn test_ladder_1009_3() {
-        test_ladder(1009, 682, 3, 7, 759);
+    fn test_ladder_3() {
+        let b: ProjEdPoint = ProjEdPoint::basepoint();
+        test_ladder_small(3, &(b + &b + &b));
     }
 
     #[test]
-    fn test_ladder_1009_5() {
-        test_ladder(1009, 682, 5, 7, 1000);
+    fn test_ladder_ignores_bit_255() {
+        let x_b = XLineProjMPoint::from(&ProjEdPoint::<Curve25519>::basepoint())
+            .normalize()
+            .x;
+        let mut scalar = [0xff; 32];
+        scalar[31] = 0x7f;
+        let expected = ladder::<Curve25519>(&scalar, &x_b);
+        scalar[31] = 0xff;
+        let (x_0, x_1) = ladder::<Curve25519>(&scalar, &x_b);
+        assert_eq!((x_0, x_1), expected);
     }
 
     #[test]
-    fn test_ladder_1009_34() {
-        test_ladder(1009, 682, 34, 7, 286);
+    fn test_ladder_consecutive() {
+        // the second output is the x-coordinate of (m + 1) * P
+        let b: ProjEdPoint = ProjEdPoint::basepoint();
+        let mut scalar = [0u8; 32];
+        scalar[0] = 5;
+        let x_b = XLineProjMPoint::from(&b).normalize().x;
+        let (_, x_1) = ladder::<Curve25519>(&scalar, &x_b);
+        let six_b = b.double() + &b.double() + &b.double();
+        assert_eq!(x_1, XLineProjMPoint::from(&six_b).normalize().x);
+    }
+
+    // RFC 7748 (section 5.2), the scalar is clamped as in X25519
+    fn test_ladder(scalar: &str, u: &str, expected: &str) {
+        let mut scalar = bytes_from_str(scalar);
+        scalar[0] &= 0b1111_1000;
+        scalar[31] &= 0b0111_1111;
+        scalar[31] |= 0b0100_0000;
+        let u = FieldElement::from_bytes(&bytes_from_str(u));
+        let (result, _) = ladder::<Curve25519>(&scalar, &u);
+        assert_eq!(result.to_bytes(), bytes_from_str(expected));
     }
 
     #[test]
-    fn test_ladder_1009_104() {
-        test_ladder(1009, 682, 104, 7, 810);
+    fn test_ladder_rfc_1() {
+        test_ladder(
+            "a546e36bf0527c9d3b16154b82465edd62144c0ac1fc5a18506a2244ba449ac4",
+            "e6db6867583030db3594c1a424b15f7c726624ec26b3353b10a903a6d0ab1c4c",
+            "c3da55379de9c6908e94ea4df28d084f32eccf03491c71f754b4075577a28552",
+        );
     }
 
     #[test]
-    fn test_ladder_1009_947() {
-        test_ladder(1009, 682, 947, 7, 755);
+    fn test_ladder_rfc_2() {
+        test_ladder(
+            "4b66e9d4d1b4673c5ad22691957d6af5c11b6421e0ea01d42ca4169e7918ba0d",
+            "e5210f12786811d3f4b7959d0538ae2c31dbe7106fc03c3efc4cd549c715a493",
+            "95cbde9476e8907d7aade45cb4b873f88b595a68799fa152e6f8f7647aac7957",
+        );
     }
 }
@@ -0,0 +1,283 @@
+use super::arithmetic::curve::Curve25519;
+use super::arithmetic::field::FieldElement;
+use super::arithmetic::xline_proj_montgomery::ladder;
+use super::keygen::gen_public_scalar_prefix;
+use super::keys::SigningKey;
+use super::sign::sign;
+use super::x25519::BASEPOINT;
+use super::Key;
+use rand::{Rng, RngCore};
+use std::fmt;
+use std::hint::black_box;
+
+// dudect-style test for timing leaks ("Dude, is my code constant time?", Reparaz et al., 2017):
+// each target runs on a fixed all-zero secret and on random secrets, in a random order, and
+// Welch's t-test compares the cycle counts of the two classes
+
+/// |t| above which a leak is reported, as in dudect
+pub const DEFAULT_THRESHOLD: f64 = 4.5;
+
+// the measurements are also tested after cropping them at these many percentiles, which removes
+// the long tail caused by interrupts and makes the test more sensitive
+const PERCENTILES: usize = 100;
+
+// cropped tests with fewer measurements are too noisy to be reported
+const MIN_CROPPED_MEASUREMENTS: f64 = 1_000.0;
+
+// number of untimed runs before the measurements, to warm up caches and branch predictors
+const WARMUP: usize = 100;
+
+/// secret-dependent operations checked by the harness
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    /// `sign::sign` on a fixed message, the secret is the private key
+    Sign,
+    /// `keygen::gen_public_scalar_prefix`, the secret is the private key
+    GenPublic,
+    /// the Montgomery ladder on the base point, the secret is the scalar
+    Ladder,
+}
+
+impl Target {
+    pub const ALL: [Self; 3] = [Self::Sign, Self::GenPublic, Self::Ladder];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Sign => "sign",
+            Self::GenPublic => "gen_public_scalar_prefix",
+            Self::Ladder => "ladder",
+        }
+    }
+
+    fn run(self, secret: &Key) {
+        match self {
+            Self::Sign => {
+                black_box(sign(&SigningKey::from_bytes(secret), b"dudect"));
+            }
+            Self::GenPublic => {
+                black_box(gen_public_scalar_prefix(secret));
+            }
+            Self::Ladder => {
+                let u = FieldElement::from_bytes(&BASEPOINT);
+                black_box(ladder::<Curve25519>(secret, &u));
+            }
+        }
+    }
+}
+
+/// online Welch's t-test between two classes of measurements, with Welford's algorithm for the
+/// means and variances
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WelchTTest {
+    n: [f64; 2],
+    mean: [f64; 2],
+    m2: [f64; 2],
+}
+
+impl WelchTTest {
+    /// add measurement x to class 0 or 1
+    pub fn push(&mut self, class: usize, x: f64) {
+        self.n[class] += 1.0;
+        let delta = x - self.mean[class];
+        self.mean[class] += delta / self.n[class];
+        self.m2[class] += delta * (x - self.mean[class]);
+    }
+
+    /// total number of measurements
+    pub fn count(&self) -> f64 {
+        self.n[0] + self.n[1]
+    }
+
+    /// Welch's t statistic, 0 if there are not enough measurements or no variance
+    pub fn t(&self) -> f64 {
+        if self.n[0] < 2.0 || self.n[1] < 2.0 {
+            return 0.0;
+        }
+        let var = [
+            self.m2[0] / (self.n[0] - 1.0),
+            self.m2[1] / (self.n[1] - 1.0),
+        ];
+        let den = (var[0] / self.n[0] + var[1] / self.n[1]).sqrt();
+        if den == 0.0 {
+            0.0
+        } else {
+            (self.mean[0] - self.mean[1]) / den
+        }
+    }
+}
+
+/// result of `measure` for one target
+#[derive(Clone, Copy, Debug)]
+pub struct Report {
+    pub target: Target,
+    pub measurements: usize,
+    /// largest |t| among the uncropped and cropped tests
+    pub max_t: f64,
+    pub threshold: f64,
+}
+
+impl Report {
+    pub fn leaks(&self) -> bool {
+        self.max_t > self.threshold
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} measurements, max |t| = {:.2}, ",
+            self.target.name(),
+            self.measurements,
+            self.max_t
+        )?;
+        if self.leaks() {
+            write!(f, "probable leak (|t| > {})", self.threshold)
+        } else {
+            write!(f, "no leak detected")
+        }
+    }
+}
+
+// timestamp counter on x86_64, nanoseconds elsewhere
+#[cfg(target_arch = "x86_64")]
+fn cycles() -> u64 {
+    // SAFETY: rdtsc is available on every x86_64 processor
+    unsafe { std::arch::x86_64::_rdtsc() }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn cycles() -> u64 {
+    use std::sync::OnceLock;
+    use std::time::Instant;
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed().as_nanos() as u64
+}
+
+/// time `measurements` runs of target on randomly interleaved fixed and random secrets, and
+/// test whether the two classes have the same timing distribution
+///
+/// with fewer than 2 measurements there is nothing to compare, and max |t| is 0
+pub fn measure(target: Target, measurements: usize, threshold: f64) -> Report {
+    if measurements < 2 {
+        return Report {
+            target,
+            measurements,
+            max_t: 0.0,
+            threshold,
+        };
+    }
+
+    let mut rng = rand::thread_rng();
+
+    // inputs are prepared beforehand so that only the target is timed
+    let classes: Vec<usize> = (0..measurements).map(|_| rng.gen_range(0..2)).collect();
+    let secrets: Vec<Key> = classes
+        .iter()
+        .map(|&class| {
+            let mut secret = [0u8; 32];
+            if class == 1 {
+                rng.fill_bytes(&mut secret);
+            }
+            secret
+        })
+        .collect();
+
+    for secret in secrets.iter().take(WARMUP) {
+        target.run(secret);
+    }
+    let times: Vec<u64> = secrets
+        .iter()
+        .map(|secret| {
+            let start = cycles();
+            target.run(secret);
+            cycles().wrapping_sub(start)
+        })
+        .collect();
+
+    // crop at the percentiles 1 - 0.5^(10 (i + 1) / PERCENTILES), as in dudect
+    let mut sorted = times.clone();
+    sorted.sort_unstable();
+    let cutoffs: Vec<u64> = (0..PERCENTILES)
+        .map(|i| {
+            let p = 1.0 - 0.5f64.powf(10.0 * (i + 1) as f64 / PERCENTILES as f64);
+            sorted[((sorted.len() as f64 * p) as usize).min(sorted.len() - 1)]
+        })
+        .collect();
+
+    let mut uncropped = WelchTTest::default();
+    let mut cropped = vec![WelchTTest::default(); PERCENTILES];
+    for (&class, &time) in classes.iter().zip(&times) {
+        uncropped.push(class, time as f64);
+        for (test, &cutoff) in cropped.iter_mut().zip(&cutoffs) {
+            if time < cutoff {
+                test.push(class, time as f64);
+            }
+        }
+    }
+
+    let max_t = cropped
+        .iter()
+        .filter(|test| test.count() >= MIN_CROPPED_MEASUREMENTS)
+        .map(|test| test.t().abs())
+        .fold(uncropped.t().abs(), f64::max);
+
+    Report {
+        target,
+        measurements,
+        max_t,
+        threshold,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn correct_welch_t_test() {
+        // class 0: 1, 2, 3, 4 (mean 2.5, variance 5/3), class 1: 2, 4, 6 (mean 4, variance 4)
+        let mut test = WelchTTest::default();
+        for x in [1.0, 2.0, 3.0, 4.0] {
+            test.push(0, x);
+        }
+        for x in [2.0, 4.0, 6.0] {
+            test.push(1, x);
+        }
+        let expected = -1.5 / (5.0 / 12.0 + 4.0 / 3.0f64).sqrt();
+        assert!((test.t() - expected).abs() < 1e-12);
+        assert_eq!(test.count(), 7.0);
+    }
+
+    #[test]
+    fn correct_welch_t_test_degenerate() {
+        let mut test = WelchTTest::default();
+        assert_eq!(test.t(), 0.0);
+        for _ in 0..10 {
+            test.push(0, 3.0);
+            test.push(1, 3.0);
+        }
+        assert_eq!(test.t(), 0.0);
+    }
+
+    #[test]
+    fn correct_report() {
+        for target in Target::ALL {
+            let report = measure(target, 200, DEFAULT_THRESHOLD);
+            assert_eq!(report.target, target);
+            assert_eq!(report.measurements, 200);
+            assert!(report.max_t.is_finite());
+            assert_eq!(report.leaks(), report.max_t > DEFAULT_THRESHOLD);
+        }
+    }
+
+    #[test]
+    fn correct_report_few_measurements() {
+        for measurements in [0, 1] {
+            let report = measure(Target::ALL[0], measurements, DEFAULT_THRESHOLD);
+            assert_eq!(report.measurements, measurements);
+            assert_eq!(report.max_t, 0.0);
+            assert!(!report.leaks());
+        }
+    }
+}
@@ -0,0 +1,382 @@
+use super::arithmetic::proj_edwards448::ProjEd448Point;
+use super::arithmetic::scalar448::Scalar448;
+use super::sign::{check_context, update_from};
+use super::{Ed448Key, Ed448Signature, Error};
+use rand::RngCore;
+use sha3::digest::{ExtendableOutput, Update, XofReader};
+use sha3::Shake256;
+use std::io::{self, Seek, SeekFrom};
+use subtle::ConstantTimeEq;
+use zeroize::{Zeroize, Zeroizing};
+
+// Ed448 (RFC 8032, section 5.2): keys are 57 bytes, signatures 114 bytes, and the hash is
+// SHAKE256 with 114 bytes of output
+
+// dom4(0, context) = "SigEd448" || 0 || len(context) || context
+fn dom4(context: &[u8]) -> Vec<u8> {
+    let mut dom = b"SigEd448".to_vec();
+    dom.push(0);
+    dom.push(context.len() as u8);
+    dom.extend_from_slice(context);
+    dom
+}
+
+fn shake256(parts: &[&[u8]]) -> [u8; 114] {
+    let mut hasher = Shake256::default();
+    for part in parts {
+        hasher.update(part);
+    }
+    finalize(hasher)
+}
+
+fn finalize(hasher: Shake256) -> [u8; 114] {
+    let mut digest = [0u8; 114];
+    hasher.finalize_xof().read(&mut digest);
+    digest
+}
+
+// returns (pruned hash[0..57], hash[57..114]), both wiped on drop
+fn expand_private(private: &Ed448Key) -> (Zeroizing<Ed448Key>, Zeroizing<Ed448Key>) {
+    let mut hash = shake256(&[private]);
+
+    let upper_bytes = Zeroizing::new(hash[57..114].try_into().unwrap());
+
+    let mut lower_bytes: Zeroizing<Ed448Key> = Zeroizing::new(hash[0..57].try_into().unwrap());
+    lower_bytes[0] &= 0b1111_1100;
+    lower_bytes[55] |= 0b1000_0000;
+    lower_bytes[56] = 0;
+
+    hash.zeroize();
+    (lower_bytes, upper_bytes)
+}
+
+// returns (public, s, hash[57..114]), the secret values are wiped on drop
+fn gen_public_scalar_prefix(
+    private: &Ed448Key,
+) -> (Ed448Key, Zeroizing<Scalar448>, Zeroizing<Ed448Key>) {
+    let (lower_bytes, upper_bytes) = expand_private(private);
+    let scalar = Zeroizing::new(Scalar448::from_bytes_mod_order(&lower_bytes));
+
+    let public = ProjEd448Point::mul_base(&scalar).encode();
+
+    (public, scalar, upper_bytes)
+}
+
+pub fn gen_public(private: &Ed448Key) -> Ed448Key {
+    let (public, _, _) = gen_public_scalar_prefix(private);
+    public
+}
+
+/// returns (private, public), the private key is wiped on drop
+pub fn generate_key_pair() -> (Zeroizing<Ed448Key>, Ed448Key) {
+    let mut private = Zeroizing::new([0u8; 57]);
+    rand::thread_rng().fill_bytes(private.as_mut());
+
+    let public = gen_public(&private);
+    (private, public)
+}
+
+pub fn sign(private: &Ed448Key, message: &[u8]) -> Ed448Signature {
+    sign_with_context(private, &[], message).unwrap()
+}
+
+/// Ed448 signature with a context of at most 255 bytes, which must also be given to
+/// `verify_with_context`, fails with `ContextTooLong` otherwise
+pub fn sign_with_context(
+    private: &Ed448Key,
+    context: &[u8],
+    message: &[u8],
+) -> Result<Ed448Signature, Error> {
+    check_context(context)?;
+    // hashing a slice cannot fail
+    Ok(sign_with_feed(private, &dom4(context), |hasher| {
+        hasher.update(message);
+        Ok(())
+    })
+    .unwrap())
+}
+
+/// `sign_with_context` on a message read from a stream (e.g. a file), in constant memory
+///
+/// the message is hashed twice, so it is read twice from its current position; fails with `Io`
+/// if reading fails, or `MessageChanged` if the message is not the same in both passes
+pub fn sign_with_context_reader<R: io::Read + Seek>(
+    private: &Ed448Key,
+    context: &[u8],
+    mut message: R,
+) -> Result<Ed448Signature, Error> {
+    check_context(context)?;
+    let start = message.stream_position()?;
+    let mut digests = vec![];
+    let signature = sign_with_feed(private, &dom4(context), |hasher| {
+        message.seek(SeekFrom::Start(start))?;
+        let mut check = Shake256::default();
+        update_from(&mut message, &mut [hasher, &mut check])?;
+        digests.push(finalize(check));
+        Ok(())
+    })?;
+
+    // same guard as for Ed25519: r and k must come from the same message
+    if digests[0] != digests[1] {
+        return Err(Error::MessageChanged);
+    }
+    Ok(signature)
+}
+
+// feed appends the message to a hash, it is called twice: for r and then for k
+fn sign_with_feed(
+    private: &Ed448Key,
+    dom: &[u8],
+    mut feed: impl FnMut(&mut Shake256) -> io::Result<()>,
+) -> io::Result<Ed448Signature> {
+    let (public, scalar, prefix) = gen_public_scalar_prefix(private);
+
+    // the nonce and the digest it comes from are wiped after use
+    let r = {
+        let mut hasher = Shake256::default();
+        hasher.update(dom);
+        hasher.update(&*prefix);
+        feed(&mut hasher)?;
+        let mut digest = finalize(hasher);
+        let r = Zeroizing::new(Scalar448::from_bytes_mod_order_wide(&digest));
+        digest.zeroize();
+        r
+    };
+
+    let r_encoding = ProjEd448Point::mul_base(&r).encode();
+
+    let k = {
+        let mut hasher = Shake256::default();
+        hasher.update(dom);
+        hasher.update(&r_encoding);
+        hasher.update(&public);
+        feed(&mut hasher)?;
+        Scalar448::from_bytes_mod_order_wide(&finalize(hasher))
+    };
+
+    let s_encoding = (*r + k * *scalar).to_bytes();
+
+    let mut signature = [0u8; 114];
+    signature[..57].copy_from_slice(&r_encoding);
+    signature[57..].copy_from_slice(&s_encoding);
+    Ok(signature)
+}
+
+pub fn verify(public: Ed448Key, message: &[u8], signature: Ed448Signature) -> bool {
+    verify_with_context(public, &[], message, signature)
+}
+
+pub fn verify_with_context(
+    public: Ed448Key,
+    context: &[u8],
+    message: &[u8],
+    signature: Ed448Signature,
+) -> bool {
+    // reading a slice cannot fail
+    verify_with_context_reader(public, context, message, signature).unwrap()
+}
+
+/// `verify_with_context` on a message read from a stream (e.g. a file), in one pass and
+/// constant memory, fails if reading fails
+pub fn verify_with_context_reader<R: io::Read>(
+    public: Ed448Key,
+    context: &[u8],
+    mut message: R,
+    signature: Ed448Signature,
+) -> io::Result<bool> {
+    if context.len() > 255 {
+        return Ok(false);
+    }
+    let r_encoding: Ed448Key = signature[0..57].try_into().unwrap();
+    let Ok(r) = ProjEd448Point::decode(&r_encoding) else {
+        return Ok(false);
+    };
+    let Some(s) = Scalar448::from_canonical_bytes(signature[57..114].try_into().unwrap()) else {
+        return Ok(false);
+    };
+    let Ok(a) = ProjEd448Point::decode(&public) else {
+        return Ok(false);
+    };
+
+    let mut hasher = Shake256::default();
+    hasher.update(&dom4(context));
+    hasher.update(&r_encoding);
+    hasher.update(&public);
+    update_from(&mut message, &mut [&mut hasher])?;
+    let k = Scalar448::from_bytes_mod_order_wide(&finalize(hasher));
+
+    // [4][s]B = [4]R + [4][k]A, the check is cofactored as required by RFC 8032
+    Ok(ProjEd448Point::mul_base(&s)
+        .mul_by_cofactor()
+        .ct_eq(&(r + &a.mul(&k)).mul_by_cofactor())
+        .into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn bytes_from_str(s: &str) -> Vec<u8> {
+        s.chars()
+            .collect::<Vec<char>>()
+            .chunks(2)
+            .map(|chunk| chunk.iter().collect::<String>())
+            .map(|byte| u8::from_str_radix(&byte, 16).unwrap())
+            .collect::<Vec<u8>>()
+    }
+
+    // test vectors from RFC 8032 (section 7.4)
+    fn correct_ed448(private: &str, public: &str, context: &str, message: &str, signature: &str) {
+        let private: Ed448Key = bytes_from_str(private).try_into().unwrap();
+        let public: Ed448Key = bytes_from_str(public).try_into().unwrap();
+        let context = bytes_from_str(context);
+        let message = bytes_from_str(message);
+        let signature: Ed448Signature = bytes_from_str(signature).try_into().unwrap();
+
+        assert_eq!(gen_public(&private), public);
+        assert_eq!(
+            sign_with_context(&private, &context, &message).unwrap(),
+            signature
+        );
+        assert!(verify_with_context(public, &context, &message, signature));
+    }
+
+    #[test]
+    fn correct_ed448_blank() {
+        let private = "6c82a562cb808d10d632be89c8513ebf6c929f34ddfa8c9f63c9960ef6e348a3528c8a3fcc2f044e39a3fc5b94492f8f032e7549a20098f95b";
+        let public = "5fd7449b59b461fd2ce787ec616ad46a1da1342485a70e1f8a0ea75d80e96778edf124769b46c7061bd6783df1e50f6cd1fa1abeafe8256180";
+        let message = "";
+        let signature = "533a37f6bbe457251f023c0d88f976ae2dfb504a843e34d2074fd823d41a591f2b233f034f628281f2fd7a22ddd47d7828c59bd0a21bfd3980ff0d2028d4b18a9df63e006c5d1c2d345b925d8dc00b4104852db99ac5c7cdda8530a113a0f4dbb61149f05a7363268c71d95808ff2e652600";
+        correct_ed448(private, public, "", message, signature);
+    }
+
+    #[test]
+    fn correct_ed448_1() {
+        let private = "c4eab05d357007c632f3dbb48489924d552b08fe0c353a0d4a1f00acda2c463afbea67c5e8d2877c5e3bc397a659949ef8021e954e0a12274e";
+        let public = "43ba28f430cdff456ae531545f7ecd0ac834a55d9358c0372bfa0c6c6798c0866aea01eb00742802b8438ea4cb82169c235160627b4c3a9480";
+        let message = "03";
+        let signature = "26b8f91727bd62897af15e41eb43c377efb9c610d48f2335cb0bd0087810f4352541b143c4b981b7e18f62de8ccdf633fc1bf037ab7cd779805e0dbcc0aae1cbcee1afb2e027df36bc04dcecbf154336c19f0af7e0a6472905e799f1953d2a0ff3348ab21aa4adafd1d234441cf807c03a00";
+        correct_ed448(private, public, "", message, signature);
+    }
+
+    #[test]
+    fn correct_ed448_1_context() {
+        let private = "c4eab05d357007c632f3dbb48489924d552b08fe0c353a0d4a1f00acda2c463afbea67c5e8d2877c5e3bc397a659949ef8021e954e0a12274e";
+        let public = "43ba28f430cdff456ae531545f7ecd0ac834a55d9358c0372bfa0c6c6798c0866aea01eb00742802b8438ea4cb82169c235160627b4c3a9480";
+        let message = "03";
+        let signature = "d4f8f6131770dd46f40867d6fd5d5055de43541f8c5e35abbcd001b32a89f7d2151f7647f11d8ca2ae279fb842d607217fce6e042f6815ea000c85741de5c8da1144a6a1aba7f96de42505d7a7298524fda538fccbbb754f578c1cad10d54d0d5428407e85dcbc98a49155c13764e66c3c00";
+        correct_ed448(private, public, "666f6f", message, signature);
+    }
+
+    #[test]
+    fn correct_ed448_11() {
+        let private = "cd23d24f714274e744343237b93290f511f6425f98e64459ff203e8985083ffdf60500553abc0e05cd02184bdb89c4ccd67e187951267eb328";
+        let public = "dcea9e78f35a1bf3499a831b10b86c90aac01cd84b67a0109b55a36e9328b1e365fce161d71ce7131a543ea4cb5f7e9f1d8b00696447001400";
+        let message = "0c3e544074ec63b0265e0c";
+        let signature = "1f0a8888ce25e8d458a21130879b840a9089d999aaba039eaf3e3afa090a09d389dba82c4ff2ae8ac5cdfb7c55e94d5d961a29fe0109941e00b8dbdeea6d3b051068df7254c0cdc129cbe62db2dc957dbb47b51fd3f213fb8698f064774250a5028961c9bf8ffd973fe5d5c206492b140e00";
+        correct_ed448(private, public, "", message, signature);
+    }
+
+    #[test]
+    fn correct_ed448_12() {
+        let private = "258cdd4ada32ed9c9ff54e63756ae582fb8fab2ac721f2c8e676a72768513d939f63dddb55609133f29adf86ec9929dccb52c1c5fd2ff7e21b";
+        let public = "3ba16da0c6f2cc1f30187740756f5e798d6bc5fc015d7c63cc9510ee3fd44adc24d8e968b6e46e6f94d19b945361726bd75e149ef09817f580";
+        let message = "64a65f3cdedcdd66811e2915";
+        let signature = "7eeeab7c4e50fb799b418ee5e3197ff6bf15d43a14c34389b59dd1a7b1b85b4ae90438aca634bea45e3a2695f1270f07fdcdf7c62b8efeaf00b45c2c96ba457eb1a8bf075a3db28e5c24f6b923ed4ad747c3c9e03c7079efb87cb110d3a99861e72003cbae6d6b8b827e4e6c143064ff3c00";
+        correct_ed448(private, public, "", message, signature);
+    }
+
+    #[test]
+    fn correct_ed448_13() {
+        let private = "7ef4e84544236752fbb56b8f31a23a10e42814f5f55ca037cdcc11c64c9a3b2949c1bb60700314611732a6c2fea98eebc0266a11a93970100e";
+        let public = "b3da079b0aa493a5772029f0467baebee5a8112d9d3a22532361da294f7bb3815c5dc59e176b4d9f381ca0938e13c6c07b174be65dfa578e80";
+        let message = "64a65f3cdedcdd66811e2915e7";
+        let signature = "6a12066f55331b6c22acd5d5bfc5d71228fbda80ae8dec26bdd306743c5027cb4890810c162c027468675ecf645a83176c0d7323a2ccde2d80efe5a1268e8aca1d6fbc194d3f77c44986eb4ab4177919ad8bec33eb47bbb5fc6e28196fd1caf56b4e7e0ba5519234d047155ac727a1053100";
+        correct_ed448(private, public, "", message, signature);
+    }
+
+    #[test]
+    fn correct_ed448_64() {
+        let private = "d65df341ad13e008567688baedda8e9dcdc17dc024974ea5b4227b6530e339bff21f99e68ca6968f3cca6dfe0fb9f4fab4fa135d5542ea3f01";
+        let public = "df9705f58edbab802c7f8363cfe5560ab1c6132c20a9f1dd163483a26f8ac53a39d6808bf4a1dfbd261b099bb03b3fb50906cb28bd8a081f00";
+        let message = "bd0f6a3747cd561bdddf4640a332461a4a30a12a434cd0bf40d766d9c6d458e5512204a30c17d1f50b5079631f64eb3112182da3005835461113718d1a5ef944";
+        let signature = "554bc2480860b49eab8532d2a533b7d578ef473eeb58c98bb2d0e1ce488a98b18dfde9b9b90775e67f47d4a1c3482058efc9f40d2ca033a0801b63d45b3b722ef552bad3b4ccb667da350192b61c508cf7b6b5adadc2c8d9a446ef003fb05cba5f30e88e36ec2703b349ca229c2670833900";
+        correct_ed448(private, public, "", message, signature);
+    }
+
+    #[test]
+    fn correct_ed448_256() {
+        let private = "2ec5fe3c17045abdb136a5e6a913e32ab75ae68b53d2fc149b77e504132d37569b7e766ba74a19bd6162343a21c8590aa9cebca9014c636df5";
+        let public = "79756f014dcfe2079f5dd9e718be4171e2ef2486a08f25186f6bff43a9936b9bfe12402b08ae65798a3d81e22e9ec80e7690862ef3d4ed3a00";
+        let message = "15777532b0bdd0d1389f636c5f6b9ba734c90af572877e2d272dd078aa1e567cfa80e12928bb542330e8409f3174504107ecd5efac61ae7504dabe2a602ede89e5cca6257a7c77e27a702b3ae39fc769fc54f2395ae6a1178cab4738e543072fc1c177fe71e92e25bf03e4ecb72f47b64d0465aaea4c7fad372536c8ba516a6039c3c2a39f0e4d832be432dfa9a706a6e5c7e19f397964ca4258002f7c0541b590316dbc5622b6b2a6fe7a4abffd96105eca76ea7b98816af0748c10df048ce012d901015a51f189f3888145c03650aa23ce894c3bd889e030d565071c59f409a9981b51878fd6fc110624dcbcde0bf7a69ccce38fabdf86f3bef6044819de11";
+        let signature = "c650ddbb0601c19ca11439e1640dd931f43c518ea5bea70d3dcde5f4191fe53f00cf966546b72bcc7d58be2b9badef28743954e3a44a23f880e8d4f1cfce2d7a61452d26da05896f0a50da66a239a8a188b6d825b3305ad77b73fbac0836ecc60987fd08527c1a8e80d5823e65cafe2a3d00";
+        correct_ed448(private, public, "", message, signature);
+    }
+
+    #[test]
+    fn correct_ed448_1023() {
+        let private = "872d093780f5d3730df7c212664b37b8a0f24f56810daa8382cd4fa3f77634ec44dc54f1c2ed9bea86fafb7632d8be199ea165f5ad55dd9ce8";
+        let public = "a81b2e8a70a5ac94ffdbcc9badfc3feb0801f258578bb114ad44ece1ec0e799da08effb81c5d685c0c56f64eecaef8cdf11cc38737838cf400";
+        let message = "6ddf802e1aae4986935f7f981ba3f0351d6273c0a0c22c9c0e8339168e675412a3debfaf435ed651558007db4384b650fcc07e3b586a27a4f7a00ac8a6fec2cd86ae4bf1570c41e6a40c931db27b2faa15a8cedd52cff7362c4e6e23daec0fbc3a79b6806e316efcc7b68119bf46bc76a26067a53f296dafdbdc11c77f7777e972660cf4b6a9b369a6665f02e0cc9b6edfad136b4fabe723d2813db3136cfde9b6d044322fee2947952e031b73ab5c603349b307bdc27bc6cb8b8bbd7bd323219b8033a581b59eadebb09b3c4f3d2277d4f0343624acc817804728b25ab797172b4c5c21a22f9c7839d64300232eb66e53f31c723fa37fe387c7d3e50bdf9813a30e5bb12cf4cd930c40cfb4e1fc622592a49588794494d56d24ea4b40c89fc0596cc9ebb961c8cb10adde976a5d602b1c3f85b9b9a001ed3c6a4d3b1437f52096cd1956d042a597d561a596ecd3d1735a8d570ea0ec27225a2c4aaff26306d1526c1af3ca6d9cf5a2c98f47e1c46db9a33234cfd4d81f2c98538a09ebe76998d0d8fd25997c7d255c6d66ece6fa56f11144950f027795e653008f4bd7ca2dee85d8e90f3dc315130ce2a00375a318c7c3d97be2c8ce5b6db41a6254ff264fa6155baee3b0773c0f497c573f19bb4f4240281f0b1f4f7be857a4e59d416c06b4c50fa09e1810ddc6b1467baeac5a3668d11b6ecaa901440016f389f80acc4db977025e7f5924388c7e340a732e554440e76570f8dd71b7d640b3450d1fd5f0410a18f9a3494f707c717b79b4bf75c98400b096b21653b5d217cf3565c9597456f70703497a078763829bc01bb1cbc8fa04eadc9a6e3f6699587a9e75c94e5bab0036e0b2e711392cff0047d0d6b05bd2a588bc109718954259f1d86678a579a3120f19cfb2963f177aeb70f2d4844826262e51b80271272068ef5b3856fa8535aa2a88b2d41f2a0e2fda7624c2850272ac4a2f561f8f2f7a318bfd5caf9696149e4ac824ad3460538fdc25421beec2cc6818162d06bbed0c40a387192349db67a118bada6cd5ab0140ee273204f628aad1c135f770279a651e24d8c14d75a6059d76b96a6fd857def5e0b354b27ab937a5815d16b5fae407ff18222c6d1ed263be68c95f32d908bd895cd76207ae726487567f9a67dad79abec316f683b17f2d02bf07e0ac8b5bc6162cf94697b3c27cd1fea49b27f23ba2901871962506520c392da8b6ad0d99f7013fbc06c2c17a569500c8a7696481c1cd33e9b14e40b82e79a5f5db82571ba97bae3ad3e0479515bb0e2b0f3bfcd1fd33034efc6245eddd7ee2086ddae2600d8ca73e214e8c2b0bdb2b047c6a464a562ed77b73d2d841c4b34973551257713b753632efba348169abc90a68f42611a40126d7cb21b58695568186f7e569d2ff0f9e745d0487dd2eb997cafc5abf9dd102e62ff66cba87";
+        let signature = "e301345a41a39a4d72fff8df69c98075a0cc082b802fc9b2b6bc503f926b65bddf7f4c8f1cb49f6396afc8a70abe6d8aef0db478d4c6b2970076c6a0484fe76d76b3a97625d79f1ce240e7c576750d295528286f719b413de9ada3e8eb78ed573603ce30d8bb761785dc30dbc320869e1a00";
+        correct_ed448(private, public, "", message, signature);
+    }
+
+    #[test]
+    fn incorrect_verify() {
+        let (private, public) = generate_key_pair();
+        let signature = sign(&private, b"message");
+        assert!(verify(public, b"message", signature));
+        assert!(!verify(public, b"massage", signature));
+        assert!(!verify_with_context(public, b"foo", b"message", signature));
+
+        let mut tampered = signature;
+        tampered[60] ^= 1;
+        assert!(!verify(public, b"message", tampered));
+
+        // S >= L
+        let mut tampered = signature;
+        tampered[57..].copy_from_slice(&[0xff; 57]);
+        assert!(!verify(public, b"message", tampered));
+    }
+
+    #[test]
+    fn correct_reader() {
+        use crate::lib::sign::test::ChangingStream;
+        use std::io::Cursor;
+
+        let (private, public) = generate_key_pair();
+        // longer than the buffer of update_from
+        let message = (0..200_000u32).map(|i| i as u8).collect::<Vec<u8>>();
+
+        let signature = sign_with_context_reader(&private, b"foo", Cursor::new(&message)).unwrap();
+        assert_eq!(
+            signature,
+            sign_with_context(&private, b"foo", &message).unwrap()
+        );
+        assert!(verify_with_context_reader(public, b"foo", &message[..], signature).unwrap());
+        assert!(!verify_with_context_reader(public, b"foo", &message[1..], signature).unwrap());
+
+        assert_eq!(
+            sign_with_context_reader(&private, &[0u8; 256], Cursor::new(&message)),
+            Err(Error::ContextTooLong)
+        );
+        let stream = ChangingStream {
+            inner: Cursor::new(b"first".to_vec()),
+            rewinds: 0,
+        };
+        assert_eq!(
+            sign_with_context_reader(&private, &[], stream),
+            Err(Error::MessageChanged)
+        );
+    }
+
+    #[test]
+    fn reject_long_context() {
+        let (private, _) = generate_key_pair();
+        assert_eq!(
+            sign_with_context(&private, &[0u8; 256], b"message"),
+            Err(Error::ContextTooLong)
+        );
+    }
+}
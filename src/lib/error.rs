@@ -0,0 +1,55 @@
+use std::{fmt, io};
+
+/// why a point, key or signature was rejected, or why a message could not be read
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// the bytes do not have the length of a key or a signature
+    InvalidLength,
+    /// the encoding of a point has y >= p (for ristretto255, s >= p or s is negative)
+    NonCanonicalY,
+    /// the encoding of a point has x = 0 with the sign bit set
+    NonCanonicalX,
+    /// no point has this y-coordinate
+    NotOnCurve,
+    /// the scalar s of a signature is not reduced (s >= L)
+    ScalarOutOfRange,
+    /// the point has small order (or, for the conversions to Montgomery coordinates, is the
+    /// identity or (0, -1), which have no affine image)
+    SmallOrder,
+    /// the point has a small-order component, it is not in the prime-order subgroup
+    NotTorsionFree,
+    /// the context is longer than 255 bytes
+    ContextTooLong,
+    /// the verification equation does not hold
+    EquationMismatch,
+    /// reading the message from a stream failed
+    Io(io::ErrorKind),
+    /// the message read twice from a stream to be signed was not the same in both passes
+    MessageChanged,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::InvalidLength => "invalid length",
+            Self::NonCanonicalY => "non-canonical point encoding (y >= p)",
+            Self::NonCanonicalX => "non-canonical point encoding (x = 0 with the sign bit set)",
+            Self::NotOnCurve => "the point is not on the curve",
+            Self::ScalarOutOfRange => "s is out of range (s >= L)",
+            Self::SmallOrder => "the point has small order",
+            Self::NotTorsionFree => "the point is not in the prime-order subgroup",
+            Self::ContextTooLong => "the context is longer than 255 bytes",
+            Self::EquationMismatch => "the verification equation does not hold",
+            Self::Io(kind) => return write!(f, "{kind}"),
+            Self::MessageChanged => "the message changed while it was signed",
+        })
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e.kind())
+    }
+}
+
+impl std::error::Error for Error {}
@@ -1,46 +1,87 @@
-use super::arithmetic::proj_edwards::get_b;
+use super::arithmetic::montgomery::MPoint;
+use super::arithmetic::proj_edwards::{ProjEdPoint, EIGHT_TORSION};
+use super::arithmetic::scalar::Scalar;
+use super::keys::{SigningKey, VerifyingKey};
 use super::Key;
 use rand::RngCore;
-use rug::{integer::Order, Integer};
 use sha2::{Digest, Sha512};
+use zeroize::{Zeroize, Zeroizing};
 
-fn prune(key: &mut Key) {
+// also the clamping of X25519 (RFC 7748)
+pub(crate) fn prune(key: &mut Key) {
     key[0] &= 0b1111_1000;
     key[31] &= 0b0111_1111;
     key[31] |= 0b0100_0000;
 }
 
-// returns (public, s, hash[32..64])
-pub(crate) fn gen_public_scalar_prefix(private: Key) -> (Key, Integer, Key) {
-    let hash = Sha512::digest(private);
+// returns (pruned hash[0..32], hash[32..64]), both wiped on drop
+pub(crate) fn expand_private(private: &Key) -> (Zeroizing<Key>, Zeroizing<Key>) {
+    let mut hash: [u8; 64] = Sha512::digest(private).into();
 
-    let upper_bytes = hash[32..64].try_into().unwrap();
+    let upper_bytes = Zeroizing::new(hash[32..64].try_into().unwrap());
 
-    let mut lower_bytes = hash[0..32].try_into().unwrap();
+    let mut lower_bytes = Zeroizing::new(hash[0..32].try_into().unwrap());
     prune(&mut lower_bytes);
-    let scalar = Integer::from_digits(&lower_bytes, Order::Lsf);
 
-    let public = {
-        let b = get_b();
-        (b * &scalar).encode()
-    };
+    hash.zeroize();
+    (lower_bytes, upper_bytes)
+}
+
+// returns (public, s, hash[32..64]), the secret values are wiped on drop
+pub(crate) fn gen_public_scalar_prefix(private: &Key) -> (Key, Zeroizing<Scalar>, Zeroizing<Key>) {
+    let (lower_bytes, upper_bytes) = expand_private(private);
+    let scalar = Zeroizing::new(Scalar::from_bytes_mod_order(*lower_bytes));
+
+    let public = ProjEdPoint::mul_base(&scalar).encode();
 
     (public, scalar, upper_bytes)
 }
 
-fn gen_public(private: Key) -> Key {
-    let (public, _, _) = gen_public_scalar_prefix(private);
-    public
+pub fn gen_public(private: &SigningKey) -> VerifyingKey {
+    let (lower_bytes, _) = expand_private(private.as_bytes());
+    let scalar = Zeroizing::new(Scalar::from_bytes_mod_order(*lower_bytes));
+    VerifyingKey::from_point(ProjEdPoint::mul_base(&scalar))
 }
 
-pub fn generate_key_pair() -> (Key, Key) {
-    let private = {
-        let mut private = [0u8; 32];
-        rand::thread_rng().fill_bytes(&mut private);
-        private
-    };
+/// returns (private, public), the private key is wiped on drop
+pub fn generate_key_pair() -> (SigningKey, VerifyingKey) {
+    let private = SigningKey::generate();
+    let public = gen_public(&private);
+    (private, public)
+}
+
+/// returns (private, public, representative), retrying until the public key has an Elligator 2
+/// representative, which looks like 32 uniformly random bytes
+///
+/// the representative is the one of A + T, with T a random point of `EIGHT_TORSION`: random bytes
+/// decode to points of any of the 8 cosets, while A alone is always in the prime-order subgroup,
+/// which anyone could check with `is_torsion_free`; `public_from_representative` gives back A
+pub fn generate_representable_key_pair() -> (SigningKey, VerifyingKey, Key) {
+    let mut rng = rand::thread_rng();
+    loop {
+        let private = SigningKey::generate();
+        let public = gen_public(&private);
+        let point = *public.point() + &EIGHT_TORSION[(rng.next_u32() % 8) as usize];
+        let Some(mut representative) = MPoint::try_from(&point)
+            .ok()
+            .and_then(|m_point| m_point.to_representative())
+        else {
+            continue;
+        };
+
+        // the two top bits are ignored by from_representative
+        representative[31] |= (rng.next_u32() as u8) & 0b1100_0000;
+        return (private, public, representative);
+    }
+}
 
-    (private, gen_public(private))
+/// public key A of a representative made by `generate_representable_key_pair`
+///
+/// the representative decodes to A + T with T of order dividing 8, A = 8^-1 * (8 * (A + T))
+/// (8^-1 modulo L) clears the torsion component
+pub fn public_from_representative(representative: &Key) -> VerifyingKey {
+    let point = ProjEdPoint::from(&MPoint::from_representative(representative));
+    VerifyingKey::from_point(point.mul_by_cofactor() * &Scalar::from(8).invert())
 }
 
 #[cfg(test)]
@@ -59,9 +100,9 @@ mod test {
     }
 
     fn correct_gen_public(private: &str, expected_public: &str) {
-        let private = key_from_str(private);
+        let private = SigningKey::from_bytes(&key_from_str(private));
         let expected_public = key_from_str(expected_public);
-        assert_eq!(gen_public(private), expected_public);
+        assert_eq!(gen_public(&private).to_bytes(), expected_public);
     }
 
     #[test]
@@ -98,4 +139,27 @@ mod test {
         let expected_public = "ec172b93ad5e563bf4932c70e1245034c35467ef2efd4d64ebf819683467e2bf";
         correct_gen_public(private, expected_public);
     }
+
+    #[test]
+    fn correct_representable_key_pair() {
+        for _ in 0..8 {
+            let (private, public, representative) = generate_representable_key_pair();
+            assert_eq!(gen_public(&private), public);
+            assert_eq!(public_from_representative(&representative), public);
+        }
+    }
+
+    #[test]
+    fn torsion_representable_key_pair() {
+        // each representative decodes to a torsion-free point with probability 1/8
+        let torsion_free = (0..64)
+            .map(|_| {
+                let (_, _, representative) = generate_representable_key_pair();
+                let m_point = MPoint::from_representative(&representative);
+                ProjEdPoint::from(&m_point).is_torsion_free()
+            })
+            .filter(|&torsion_free| torsion_free)
+            .count();
+        assert!(torsion_free < 64);
+    }
 }
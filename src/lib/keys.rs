@@ -0,0 +1,414 @@
+use super::arithmetic::proj_edwards::ProjEdPoint;
+use super::arithmetic::scalar::Scalar;
+use super::keygen::{expand_private, gen_public};
+use super::secret::SecretBuffer;
+use super::sign::{check_context, dom2, prehash_reader, sign_with_dom, sign_with_dom_reader};
+use super::{Error, Key};
+use sha2::{Digest, Sha512};
+use std::fmt;
+use std::io::{Read, Seek};
+use zeroize::Zeroizing;
+
+/// Ed25519 private key (the 32-byte seed of RFC 8032), kept in a `SecretBuffer` which is wiped
+/// on drop
+///
+/// its `Debug` implementation never prints the key
+pub struct SigningKey {
+    seed: SecretBuffer,
+}
+
+impl SigningKey {
+    pub fn from_bytes(bytes: &Key) -> Self {
+        // a buffer which is not locked cannot fail
+        let mut seed = SecretBuffer::new(false).unwrap();
+        seed.copy_from_slice(bytes);
+        Self { seed }
+    }
+
+    /// the seed, wiped on drop
+    pub fn to_bytes(&self) -> Zeroizing<Key> {
+        Zeroizing::new(*self.seed)
+    }
+
+    pub fn as_bytes(&self) -> &Key {
+        &self.seed
+    }
+
+    /// random private key
+    pub fn generate() -> Self {
+        Self {
+            seed: SecretBuffer::random(false).unwrap(),
+        }
+    }
+
+    pub fn verifying_key(&self) -> VerifyingKey {
+        gen_public(self)
+    }
+
+    /// to sign many messages, see `ExpandedSecretKey`
+    pub fn expand(&self) -> ExpandedSecretKey {
+        ExpandedSecretKey::from(self)
+    }
+}
+
+/// keeps the buffer, and its lock, e.g. for a key read in place from a file
+impl From<SecretBuffer> for SigningKey {
+    fn from(seed: SecretBuffer) -> Self {
+        Self { seed }
+    }
+}
+
+impl TryFrom<&[u8]> for SigningKey {
+    type Error = Error;
+
+    /// fails with `InvalidLength` if bytes is not 32 bytes long
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(Self::from_bytes(
+            bytes.try_into().map_err(|_| Error::InvalidLength)?,
+        ))
+    }
+}
+
+// never print the key
+impl fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SigningKey").finish_non_exhaustive()
+    }
+}
+
+/// Ed25519 private key expanded once: the pruned scalar, the nonce prefix (the second half of
+/// SHA-512(seed)) and the public key
+///
+/// `sign` on a `SigningKey` hashes the seed and computes the public key again at every call, this
+/// only computes R, so it is twice as fast when signing many messages with the same key
+pub struct ExpandedSecretKey {
+    scalar: Zeroizing<Scalar>,
+    prefix: Zeroizing<Key>,
+    public: VerifyingKey,
+}
+
+impl ExpandedSecretKey {
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.public
+    }
+
+    pub(crate) fn scalar(&self) -> &Scalar {
+        &self.scalar
+    }
+
+    pub(crate) fn prefix(&self) -> &Key {
+        &self.prefix
+    }
+
+    /// pure Ed25519, same as `sign::sign`
+    pub fn sign(&self, message: &[u8]) -> Signature {
+        sign_with_dom(self, &[], message)
+    }
+
+    /// Ed25519ctx, same as `sign::sign_with_context`
+    pub fn sign_with_context(&self, context: &[u8], message: &[u8]) -> Result<Signature, Error> {
+        check_context(context)?;
+        Ok(sign_with_dom(self, &dom2(0, context), message))
+    }
+
+    /// Ed25519ph, same as `sign::sign_prehashed`
+    pub fn sign_prehashed(&self, context: &[u8], message: &[u8]) -> Result<Signature, Error> {
+        check_context(context)?;
+        let prehash = Sha512::digest(message);
+        Ok(sign_with_dom(self, &dom2(1, context), &prehash))
+    }
+
+    /// `sign` on a message read from a stream (e.g. a file), in constant memory
+    ///
+    /// pure Ed25519 hashes the message twice, so it is read twice from its current position;
+    /// fails with `Io` if reading fails, or `MessageChanged` if the message is not the same in
+    /// both passes
+    pub fn sign_reader<R: Read + Seek>(&self, message: R) -> Result<Signature, Error> {
+        sign_with_dom_reader(self, &[], message)
+    }
+
+    /// `sign_with_context` on a message read twice from a stream, see `sign_reader`
+    pub fn sign_with_context_reader<R: Read + Seek>(
+        &self,
+        context: &[u8],
+        message: R,
+    ) -> Result<Signature, Error> {
+        check_context(context)?;
+        sign_with_dom_reader(self, &dom2(0, context), message)
+    }
+
+    /// `sign_prehashed` on a message read once from a stream, in constant memory
+    pub fn sign_prehashed_reader<R: Read>(
+        &self,
+        context: &[u8],
+        message: R,
+    ) -> Result<Signature, Error> {
+        check_context(context)?;
+        let prehash = prehash_reader(message)?;
+        Ok(sign_with_dom(self, &dom2(1, context), &prehash))
+    }
+}
+
+impl From<&SigningKey> for ExpandedSecretKey {
+    fn from(private: &SigningKey) -> Self {
+        let (lower_bytes, prefix) = expand_private(private.as_bytes());
+        let scalar = Zeroizing::new(Scalar::from_bytes_mod_order(*lower_bytes));
+        let public = VerifyingKey::from_point(ProjEdPoint::mul_base(&scalar));
+        Self {
+            scalar,
+            prefix,
+            public,
+        }
+    }
+}
+
+// never print the secret values
+impl fmt::Debug for ExpandedSecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ExpandedSecretKey")
+            .field("public", &self.public)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Ed25519 public key, decoded once: the point A and its encoding, which is what gets hashed
+#[derive(Clone, Copy, Debug)]
+pub struct VerifyingKey {
+    point: ProjEdPoint,
+    bytes: Key,
+    canonical: bool,
+}
+
+impl VerifyingKey {
+    /// fails with `NotOnCurve` if bytes is not the encoding of a point
+    ///
+    /// non-canonical encodings (y >= p, or x = 0 with the sign bit set) are decoded, but only
+    /// `VerificationPolicy::Zip215` accepts signatures for them
+    pub fn from_bytes(bytes: &Key) -> Result<Self, Error> {
+        let (point, canonical) = decode_point(bytes)?;
+        Ok(Self {
+            point,
+            bytes: *bytes,
+            canonical,
+        })
+    }
+
+    // the public key of a private key, whose encoding is canonical
+    pub(crate) fn from_point(mut point: ProjEdPoint) -> Self {
+        Self {
+            bytes: point.encode(),
+            point,
+            canonical: true,
+        }
+    }
+
+    /// the encoding the key was decoded from
+    pub fn to_bytes(&self) -> Key {
+        self.bytes
+    }
+
+    pub fn as_bytes(&self) -> &Key {
+        &self.bytes
+    }
+
+    pub fn point(&self) -> &ProjEdPoint {
+        &self.point
+    }
+
+    pub fn is_canonical(&self) -> bool {
+        self.canonical
+    }
+}
+
+impl PartialEq for VerifyingKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+impl Eq for VerifyingKey {}
+
+impl TryFrom<&[u8]> for VerifyingKey {
+    type Error = Error;
+
+    /// fails if bytes is not 32 bytes long (`InvalidLength`) or not the encoding of a point
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        Self::from_bytes(bytes.try_into().map_err(|_| Error::InvalidLength)?)
+    }
+}
+
+/// Ed25519 signature (R, s), with R decoded and s < L
+#[derive(Clone, Copy, Debug)]
+pub struct Signature {
+    r: ProjEdPoint,
+    r_bytes: Key,
+    r_canonical: bool,
+    s: Scalar,
+}
+
+impl Signature {
+    /// fails if the first 32 bytes are not the encoding of a point (`NotOnCurve`), or if the
+    /// last 32 bytes are not a canonical scalar (`ScalarOutOfRange`, s >= L), which no
+    /// verification policy accepts
+    ///
+    /// as for `VerifyingKey`, non-canonical encodings of R are decoded
+    pub fn from_bytes(bytes: &[u8; 64]) -> Result<Self, Error> {
+        let r_bytes: Key = bytes[..32].try_into().unwrap();
+        let (r, r_canonical) = decode_point(&r_bytes)?;
+        let s = Scalar::from_canonical_bytes(bytes[32..].try_into().unwrap())
+            .ok_or(Error::ScalarOutOfRange)?;
+        Ok(Self {
+            r,
+            r_bytes,
+            r_canonical,
+            s,
+        })
+    }
+
+    // signature computed by `sign`, whose encoding of R is canonical
+    pub(crate) fn from_parts(r_bytes: Key, r: ProjEdPoint, s: Scalar) -> Self {
+        Self {
+            r,
+            r_bytes,
+            r_canonical: true,
+            s,
+        }
+    }
+
+    /// R || s
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&self.r_bytes);
+        bytes[32..].copy_from_slice(&self.s.to_bytes());
+        bytes
+    }
+
+    pub fn r(&self) -> &ProjEdPoint {
+        &self.r
+    }
+
+    /// the encoding R was decoded from, which is what gets hashed
+    pub fn r_bytes(&self) -> &Key {
+        &self.r_bytes
+    }
+
+    pub fn s(&self) -> &Scalar {
+        &self.s
+    }
+
+    pub fn is_canonical(&self) -> bool {
+        self.r_canonical
+    }
+}
+
+impl PartialEq for Signature {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_bytes() == other.to_bytes()
+    }
+}
+
+impl Eq for Signature {}
+
+impl TryFrom<&[u8]> for Signature {
+    type Error = Error;
+
+    /// fails if bytes is not 64 bytes long (`InvalidLength`) or is not a valid signature, see
+    /// `from_bytes`
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        Self::from_bytes(bytes.try_into().map_err(|_| Error::InvalidLength)?)
+    }
+}
+
+// returns (point, whether the encoding is canonical)
+fn decode_point(bytes: &Key) -> Result<(ProjEdPoint, bool), Error> {
+    let mut point = ProjEdPoint::decode_non_canonical(*bytes)?;
+    let canonical = point.encode() == *bytes;
+    Ok((point, canonical))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn redacted_signing_key() {
+        let private = SigningKey::from_bytes(&[0x42; 32]);
+        assert_eq!(format!("{private:?}"), "SigningKey { .. }");
+    }
+
+    #[test]
+    fn correct_expanded_secret_key() {
+        let private = SigningKey::generate();
+        let expanded = private.expand();
+        assert_eq!(expanded.verifying_key(), private.verifying_key());
+        assert!(!format!("{expanded:?}").contains("scalar"));
+
+        use crate::lib::sign::{sign, sign_prehashed, sign_with_context};
+        for message in [&b""[..], b"message", &[0x42; 1000]] {
+            assert_eq!(expanded.sign(message), sign(&private, message));
+            assert_eq!(
+                expanded.sign_with_context(b"foo", message),
+                sign_with_context(&private, b"foo", message)
+            );
+            assert_eq!(
+                expanded.sign_prehashed(b"", message),
+                sign_prehashed(&private, b"", message)
+            );
+        }
+        assert!(expanded.sign_with_context(&[0; 256], b"").is_err());
+        assert!(expanded.sign_prehashed(&[0; 256], b"").is_err());
+    }
+
+    #[test]
+    fn correct_round_trips() {
+        let private = SigningKey::generate();
+        assert_eq!(
+            *SigningKey::try_from(&private.to_bytes()[..])
+                .unwrap()
+                .to_bytes(),
+            *private.to_bytes()
+        );
+
+        let public = private.verifying_key();
+        assert_eq!(VerifyingKey::from_bytes(public.as_bytes()).unwrap(), public);
+        assert_eq!(VerifyingKey::try_from(&public.to_bytes()[..]), Ok(public));
+
+        let signature = crate::lib::sign::sign(&private, b"message");
+        let bytes = signature.to_bytes();
+        assert_eq!(Signature::from_bytes(&bytes), Ok(signature));
+        assert_eq!(Signature::try_from(&bytes[..]), Ok(signature));
+    }
+
+    #[test]
+    fn reject_invalid_bytes() {
+        assert!(SigningKey::try_from(&[0u8; 31][..]).is_err());
+        assert!(VerifyingKey::try_from(&[0u8; 33][..]).is_err());
+        assert!(Signature::try_from(&[0u8; 63][..]).is_err());
+
+        // y = 2 is not the y-coordinate of a point
+        let mut y = [0u8; 32];
+        y[0] = 2;
+        assert!(VerifyingKey::from_bytes(&y).is_err());
+
+        // s = L
+        let mut signature = [0u8; 64];
+        signature[0] = 1;
+        signature[32..].copy_from_slice(&[
+            0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9,
+            0xde, 0x14, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x10,
+        ]);
+        assert!(Signature::from_bytes(&signature).is_err());
+    }
+
+    #[test]
+    fn keep_non_canonical_encodings() {
+        // y = p + 1 encodes the identity
+        let mut bytes = [0xff; 32];
+        bytes[0] = 0xee;
+        bytes[31] = 0x7f;
+        let public = VerifyingKey::from_bytes(&bytes).unwrap();
+        assert!(!public.is_canonical());
+        assert!(public.point().is_identity());
+        assert_eq!(public.to_bytes(), bytes);
+    }
+}
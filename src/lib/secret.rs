@@ -0,0 +1,191 @@
+use rand::RngCore;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::ops::{Deref, DerefMut};
+use std::path::Path;
+use zeroize::Zeroize;
+
+/// secret key on the heap, wiped on drop, and optionally locked in memory with mlock(2) so that
+/// it is never written to swap
+///
+/// the key stays at the same address for its whole lifetime, so that the locked page is the one
+/// actually holding it
+///
+/// N is the length of the key: 32 bytes for Ed25519 and X25519, 57 for Ed448
+pub struct SecretBuffer<const N: usize = 32> {
+    key: Box<[u8; N]>,
+    locked: bool,
+}
+
+impl<const N: usize> SecretBuffer<N> {
+    /// all-zero buffer, locked in memory if lock is true
+    ///
+    /// fails with the error of mlock(2) if the memory cannot be locked, e.g. because of
+    /// RLIMIT_MEMLOCK
+    pub fn new(lock: bool) -> io::Result<Self> {
+        let mut buffer = Self {
+            key: Box::new([0u8; N]),
+            locked: false,
+        };
+        if lock {
+            mlock(buffer.key.as_ref())?;
+            buffer.locked = true;
+        }
+        Ok(buffer)
+    }
+
+    /// buffer filled with random bytes, e.g. a new private key
+    pub fn random(lock: bool) -> io::Result<Self> {
+        let mut buffer = Self::new(lock)?;
+        rand::thread_rng().fill_bytes(buffer.key.as_mut());
+        Ok(buffer)
+    }
+
+    /// buffer holding a key file, read in place so that the key is never copied elsewhere
+    ///
+    /// fails with `InvalidData` if the file does not contain exactly N bytes
+    pub fn from_file<P: AsRef<Path>>(path: P, lock: bool) -> io::Result<Self> {
+        let mut file = fs::File::open(path)?;
+        let mut buffer = Self::new(lock).map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("failed locking the secret key in memory: {e}"),
+            )
+        })?;
+        let invalid_length = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("the key is not {N} bytes long"),
+            )
+        };
+        match file.read_exact(&mut buffer[..]) {
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Err(invalid_length()),
+            result => result?,
+        }
+        if file.read(&mut [0u8; 1])? != 0 {
+            return Err(invalid_length());
+        }
+        Ok(buffer)
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+}
+
+impl<const N: usize> Deref for SecretBuffer<N> {
+    type Target = [u8; N];
+    fn deref(&self) -> &[u8; N] {
+        &self.key
+    }
+}
+
+impl<const N: usize> DerefMut for SecretBuffer<N> {
+    fn deref_mut(&mut self) -> &mut [u8; N] {
+        &mut self.key
+    }
+}
+
+impl<const N: usize> Drop for SecretBuffer<N> {
+    fn drop(&mut self) {
+        self.key.zeroize();
+        if self.locked {
+            // the buffer is wiped, so failing to unlock it is harmless
+            let _ = munlock(self.key.as_ref());
+        }
+    }
+}
+
+// never print the key
+impl<const N: usize> fmt::Debug for SecretBuffer<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SecretBuffer")
+            .field("locked", &self.locked)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(unix)]
+fn mlock(bytes: &[u8]) -> io::Result<()> {
+    // SAFETY: bytes is a valid allocation of bytes.len() bytes
+    match unsafe { libc::mlock(bytes.as_ptr().cast(), bytes.len()) } {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+#[cfg(unix)]
+fn munlock(bytes: &[u8]) -> io::Result<()> {
+    // SAFETY: bytes is a valid allocation of bytes.len() bytes
+    match unsafe { libc::munlock(bytes.as_ptr().cast(), bytes.len()) } {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+// memory locking is only supported on unix
+#[cfg(not(unix))]
+fn mlock(_bytes: &[u8]) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "memory locking is only supported on unix",
+    ))
+}
+
+#[cfg(not(unix))]
+fn munlock(_bytes: &[u8]) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn correct_secret_buffer() {
+        let mut buffer: SecretBuffer = SecretBuffer::new(false).unwrap();
+        assert_eq!(*buffer, [0u8; 32]);
+        assert!(!buffer.is_locked());
+        buffer[0] = 42;
+        assert_eq!(buffer[0], 42);
+        assert_ne!(*SecretBuffer::random(false).unwrap(), [0u8; 32]);
+
+        let buffer: SecretBuffer<57> = SecretBuffer::new(false).unwrap();
+        assert_eq!(*buffer, [0u8; 57]);
+    }
+
+    #[test]
+    fn redacted_debug() {
+        let mut buffer: SecretBuffer = SecretBuffer::new(false).unwrap();
+        buffer.copy_from_slice(&[0xab; 32]);
+        assert_eq!(format!("{buffer:?}"), "SecretBuffer { locked: false, .. }");
+    }
+
+    #[test]
+    fn correct_from_file() {
+        let path = std::env::temp_dir().join(format!("secret_buffer_{}", std::process::id()));
+        fs::write(&path, [7u8; 32]).unwrap();
+        assert_eq!(
+            *SecretBuffer::<32>::from_file(&path, false).unwrap(),
+            [7u8; 32]
+        );
+
+        // the file must contain exactly N bytes
+        for length in [31, 33] {
+            fs::write(&path, vec![7u8; length]).unwrap();
+            let error = SecretBuffer::<32>::from_file(&path, false).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+        fs::remove_file(&path).unwrap();
+        assert!(SecretBuffer::<32>::from_file(&path, false).is_err());
+    }
+
+    // a single page is always below the default RLIMIT_MEMLOCK
+    #[cfg(unix)]
+    #[test]
+    fn correct_lock() {
+        let buffer: SecretBuffer = SecretBuffer::random(true).unwrap();
+        assert!(buffer.is_locked());
+    }
+}
@@ -1,49 +1,157 @@
-use super::arithmetic::proj_edwards::{get_b, get_l};
-use super::keygen::gen_public_scalar_prefix;
-use super::{Key, Signature};
-use rug::{integer::Order, Integer};
+use super::arithmetic::proj_edwards::ProjEdPoint;
+use super::arithmetic::scalar::Scalar;
+use super::keys::{ExpandedSecretKey, Signature, SigningKey};
+use super::Error;
 use sha2::{Digest, Sha512};
+use std::io::{self, Read, Seek, SeekFrom};
+use zeroize::{Zeroize, Zeroizing};
 
-pub fn sign(private: Key, message: &[u8]) -> Signature {
-    let l = get_l();
-    let b = get_b();
+// dom2(phflag, context) = "SigEd25519 no Ed25519 collisions" || phflag || len(context) ||
+// context, prepended to the hashed data by Ed25519ctx (phflag = 0) and Ed25519ph (phflag = 1)
+pub(crate) fn dom2(phflag: u8, context: &[u8]) -> Vec<u8> {
+    let mut dom = b"SigEd25519 no Ed25519 collisions".to_vec();
+    dom.push(phflag);
+    dom.push(context.len() as u8);
+    dom.extend_from_slice(context);
+    dom
+}
 
-    let (public, scalar, prefix) = gen_public_scalar_prefix(private);
+/// pure Ed25519 (RFC 8032, section 5.1), without domain separation
+///
+/// expands the private key first, use `ExpandedSecretKey::sign` to sign many messages
+pub fn sign(private: &SigningKey, message: &[u8]) -> Signature {
+    private.expand().sign(message)
+}
 
-    let r = {
-        let mut bytes = prefix.to_vec();
-        bytes.append(&mut message.to_vec());
-        let digest = Sha512::digest(&bytes);
-        Integer::from_digits(&digest, Order::Lsf)
+/// Ed25519ctx: Ed25519 with a context of at most 255 bytes, which must also be given to
+/// `verify_with_context`
+///
+/// the RFC recommends a non-empty context, use `sign` otherwise
+pub fn sign_with_context(
+    private: &SigningKey,
+    context: &[u8],
+    message: &[u8],
+) -> Result<Signature, Error> {
+    private.expand().sign_with_context(context, message)
+}
+
+/// Ed25519ph: Ed25519 on the SHA-512 hash of the message, with a context of at most 255 bytes
+/// (possibly empty), which must also be given to `verify_prehashed`
+pub fn sign_prehashed(
+    private: &SigningKey,
+    context: &[u8],
+    message: &[u8],
+) -> Result<Signature, Error> {
+    private.expand().sign_prehashed(context, message)
+}
+
+// dom is prepended to both hashes, it is empty for pure Ed25519
+pub(crate) fn sign_with_dom(private: &ExpandedSecretKey, dom: &[u8], message: &[u8]) -> Signature {
+    // hashing a slice cannot fail
+    sign_with_feed(private, dom, |hasher| {
+        hasher.update(message);
+        Ok(())
+    })
+    .unwrap()
+}
+
+// same as sign_with_dom, the message is read twice from a stream, from its current position
+pub(crate) fn sign_with_dom_reader<R: Read + Seek>(
+    private: &ExpandedSecretKey,
+    dom: &[u8],
+    mut message: R,
+) -> Result<Signature, Error> {
+    let start = message.stream_position()?;
+    let mut digests = vec![];
+    let signature = sign_with_feed(private, dom, |hasher| {
+        message.seek(SeekFrom::Start(start))?;
+        let mut check = Sha512::new();
+        update_from(&mut message, &mut [hasher, &mut check])?;
+        digests.push(check.finalize());
+        Ok(())
+    })?;
+
+    // r comes from the first pass and k from the second: if the message changed in between, two
+    // such signatures could share r with different k, which gives away the private scalar
+    if digests[0] != digests[1] {
+        return Err(Error::MessageChanged);
+    }
+    Ok(signature)
+}
+
+// the context of Ed25519ctx and Ed25519ph is at most 255 bytes
+pub(crate) fn check_context(context: &[u8]) -> Result<(), Error> {
+    if context.len() > 255 {
+        return Err(Error::ContextTooLong);
     }
-    .modulo(&l);
+    Ok(())
+}
 
-    let r_encoding = (b * &r).encode();
+/// SHA-512 of a message read from a stream, as hashed by Ed25519ph
+pub(crate) fn prehash_reader<R: Read>(mut message: R) -> io::Result<[u8; 64]> {
+    let mut hasher = Sha512::new();
+    update_from(&mut message, &mut [&mut hasher])?;
+    Ok(hasher.finalize().into())
+}
 
-    let k = {
-        let mut bytes = r_encoding.to_vec();
-        bytes.append(&mut public.to_vec());
-        bytes.append(&mut message.to_vec());
-        let digest = Sha512::digest(&bytes);
-        Integer::from_digits(&digest, Order::Lsf)
-    }
-    .modulo(&l);
-
-    let mut s_encoding = {
-        let s_int = (r + k * scalar).modulo(&l);
-        let mut digits = Integer::to_digits(&s_int, Order::Lsf);
-        digits.resize(32, 0);
-        digits
+// feeds the rest of message to every hasher, in constant memory
+pub(crate) fn update_from<R: Read, H: sha2::digest::Update>(
+    message: &mut R,
+    hashers: &mut [&mut H],
+) -> io::Result<()> {
+    let mut buffer = [0u8; 1 << 16];
+    loop {
+        let n = match message.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        for hasher in hashers.iter_mut() {
+            hasher.update(&buffer[..n]);
+        }
+    }
+}
+
+// feed appends the message to a hash, it is called twice: for r and then for k
+fn sign_with_feed(
+    private: &ExpandedSecretKey,
+    dom: &[u8],
+    mut feed: impl FnMut(&mut Sha512) -> io::Result<()>,
+) -> io::Result<Signature> {
+    // the nonce and the digest it comes from are wiped after use
+    let r = {
+        let mut hasher = Sha512::new()
+            .chain_update(dom)
+            .chain_update(private.prefix());
+        feed(&mut hasher)?;
+        let mut digest: [u8; 64] = hasher.finalize().into();
+        let r = Zeroizing::new(Scalar::from_bytes_mod_order_wide(&digest));
+        digest.zeroize();
+        r
     };
 
-    let mut signature = r_encoding.to_vec();
-    signature.append(&mut s_encoding);
+    let mut r_point = ProjEdPoint::mul_base(&r);
+    let r_encoding = r_point.encode();
 
-    signature.try_into().unwrap()
+    let k = {
+        let mut hasher = Sha512::new()
+            .chain_update(dom)
+            .chain_update(r_encoding)
+            .chain_update(private.verifying_key().as_bytes());
+        feed(&mut hasher)?;
+        Scalar::from_bytes_mod_order_wide(&hasher.finalize().into())
+    };
+
+    Ok(Signature::from_parts(
+        r_encoding,
+        r_point,
+        *r + k * *private.scalar(),
+    ))
 }
 
 #[cfg(test)]
-mod test {
+pub(crate) mod test {
     use super::*;
 
     fn bytes_from_str(s: &str) -> Vec<u8> {
@@ -56,11 +164,30 @@ mod test {
     }
 
     fn correct_sign(private: &str, message: &str, expected_signature: &str) {
-        let private = bytes_from_str(private).try_into().unwrap();
+        let private = SigningKey::try_from(&bytes_from_str(private)[..]).unwrap();
         let message = bytes_from_str(message);
-        let expected_signature: Signature = bytes_from_str(expected_signature).try_into().unwrap();
+        let expected_signature =
+            Signature::try_from(&bytes_from_str(expected_signature)[..]).unwrap();
 
-        assert_eq!(sign(private, &message), expected_signature);
+        assert_eq!(sign(&private, &message), expected_signature);
+    }
+
+    fn correct_sign_with_context(
+        private: &str,
+        context: &str,
+        message: &str,
+        expected_signature: &str,
+    ) {
+        let private = SigningKey::try_from(&bytes_from_str(private)[..]).unwrap();
+        let context = bytes_from_str(context);
+        let message = bytes_from_str(message);
+        let expected_signature =
+            Signature::try_from(&bytes_from_str(expected_signature)[..]).unwrap();
+
+        assert_eq!(
+            sign_with_context(&private, &context, &message),
+            Ok(expected_signature)
+        );
     }
 
     #[test]
@@ -102,4 +229,130 @@ mod test {
         let expected_signature = "dc2a4459e7369633a52b1bf277839a00201009a3efbf3ecb69bea2186c26b58909351fc9ac90b3ecfdfbc7c66431e0303dca179c138ac17ad9bef1177331a704";
         correct_sign(private, message, expected_signature);
     }
+
+    // RFC 8032, section 7.2
+    #[test]
+    fn correct_sign_ctx_foo() {
+        let private = "0305334e381af78f141cb666f6199f57bc3495335a256a95bd2a55bf546663f6";
+        let message = "f726936d19c800494e3fdaff20b276a8";
+        let expected_signature = "55a4cc2f70a54e04288c5f4cd1e45a7bb520b36292911876cada7323198dd87a8b36950b95130022907a7fb7c4e9b2d5f6cca685a587b4b21f4b888e4e7edb0d";
+        correct_sign_with_context(private, "666f6f", message, expected_signature);
+    }
+
+    #[test]
+    fn correct_sign_ctx_bar() {
+        let private = "0305334e381af78f141cb666f6199f57bc3495335a256a95bd2a55bf546663f6";
+        let message = "f726936d19c800494e3fdaff20b276a8";
+        let expected_signature = "fc60d5872fc46b3aa69f8b5b4351d5808f92bcc044606db097abab6dbcb1aee3216c48e8b3b66431b5b186d1d28f8ee15a5ca2df6668346291c2043d4eb3e90d";
+        correct_sign_with_context(private, "626172", message, expected_signature);
+    }
+
+    #[test]
+    fn correct_sign_ctx_other_message() {
+        let private = "0305334e381af78f141cb666f6199f57bc3495335a256a95bd2a55bf546663f6";
+        let message = "508e9e6882b979fea900f62adceaca35";
+        let expected_signature = "8b70c1cc8310e1de20ac53ce28ae6e7207f33c3295e03bb5c0732a1d20dc64908922a8b052cf99b7c4fe107a5abb5b2c4085ae75890d02df26269d8945f84b0b";
+        correct_sign_with_context(private, "666f6f", message, expected_signature);
+    }
+
+    #[test]
+    fn correct_sign_ctx_other_key() {
+        let private = "ab9c2853ce297ddab85c993b3ae14bcad39b2c682beabc27d6d4eb20711d6560";
+        let message = "f726936d19c800494e3fdaff20b276a8";
+        let expected_signature = "21655b5f1aa965996b3f97b3c849eafba922a0a62992f73b3d1b73106a84ad85e9b86a7b6005ea868337ff2d20a7f5fbd4cd10b0be49a68da2b2e0dc0ad8960f";
+        correct_sign_with_context(private, "666f6f", message, expected_signature);
+    }
+
+    // RFC 8032, section 7.3
+    #[test]
+    fn correct_sign_ph() {
+        let private = SigningKey::try_from(
+            &bytes_from_str("833fe62409237b9d62ec77587520911e9a759cec1d19755b7da901b96dca3d42")[..],
+        )
+        .unwrap();
+        let expected_signature = Signature::try_from(&bytes_from_str("98a70222f0b8121aa9d30f813d683f809e462b469c7ff87639499bb94e6dae4131f85042463c2a355a2003d062adf5aaa10b8c61e636062aaad11c2a26083406")[..]).unwrap();
+        assert_eq!(
+            sign_prehashed(&private, &[], b"abc"),
+            Ok(expected_signature)
+        );
+    }
+
+    #[test]
+    fn reject_long_context() {
+        let private = SigningKey::from_bytes(&[0u8; 32]);
+        assert!(sign_with_context(&private, &[0u8; 256], b"").is_err());
+        assert!(sign_prehashed(&private, &[0u8; 256], b"").is_err());
+        assert!(sign_with_context(&private, &[0u8; 255], b"").is_ok());
+    }
+
+    #[test]
+    fn correct_sign_reader() {
+        use std::io::Cursor;
+
+        let private = SigningKey::generate().expand();
+        // longer than the buffer of update_from
+        let message = (0..200_000u32).map(|i| i as u8).collect::<Vec<u8>>();
+
+        assert_eq!(
+            private.sign_reader(Cursor::new(&message)).unwrap(),
+            private.sign(&message)
+        );
+        assert_eq!(
+            private
+                .sign_with_context_reader(b"foo", Cursor::new(&message))
+                .unwrap(),
+            private.sign_with_context(b"foo", &message).unwrap()
+        );
+        assert_eq!(
+            private.sign_prehashed_reader(b"foo", &message[..]).unwrap(),
+            private.sign_prehashed(b"foo", &message).unwrap()
+        );
+
+        // the message starts at the current position of the stream
+        let mut stream = Cursor::new(&message);
+        stream.set_position(1000);
+        assert_eq!(
+            private.sign_reader(&mut stream).unwrap(),
+            private.sign(&message[1000..])
+        );
+
+        assert_eq!(
+            private.sign_with_context_reader(&[0u8; 256], Cursor::new(&message)),
+            Err(Error::ContextTooLong)
+        );
+    }
+
+    // a stream whose content changes after it is rewound for the second time
+    pub(crate) struct ChangingStream {
+        pub(crate) inner: io::Cursor<Vec<u8>>,
+        pub(crate) rewinds: usize,
+    }
+
+    impl Read for ChangingStream {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.inner.read(buf)
+        }
+    }
+
+    impl Seek for ChangingStream {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            if let SeekFrom::Start(_) = pos {
+                self.rewinds += 1;
+            }
+            if self.rewinds == 2 {
+                self.inner = io::Cursor::new(b"second".to_vec());
+            }
+            self.inner.seek(pos)
+        }
+    }
+
+    #[test]
+    fn reject_changing_stream() {
+        let private = SigningKey::generate().expand();
+        let stream = ChangingStream {
+            inner: io::Cursor::new(b"first".to_vec()),
+            rewinds: 0,
+        };
+        assert_eq!(private.sign_reader(stream), Err(Error::MessageChanged));
+    }
 }
@@ -1,32 +1,285 @@
-use super::arithmetic::proj_edwards::{get_b, get_l, ProjEdPoint};
-use super::{Key, Signature};
-use rug::{integer::Order, Integer};
+use super::arithmetic::curve::Curve25519;
+use super::arithmetic::proj_edwards::ProjEdPoint;
+use super::arithmetic::scalar::Scalar;
+use super::keys::{Signature, VerifyingKey};
+use super::sign::{check_context, dom2, prehash_reader, update_from};
+use super::{Error, Key};
+use rand::RngCore;
 use sha2::{Digest, Sha512};
+use std::io::Read;
+use subtle::ConstantTimeEq;
 
-pub fn verify(public: Key, message: &[u8], signature: Signature) -> bool {
-    let Ok(r) = ProjEdPoint::decode(signature[0..32].try_into().unwrap()) else {
-        return false;
-    };
-    let s = {
-        let bytes: Key = signature[32..64].try_into().unwrap();
-        let s = Integer::from_digits(&bytes, Order::Lsf);
-        if s >= get_l() {
-            return false;
+/// which signatures are valid, implementations of Ed25519 disagree on the edge cases (see
+/// "Taming the many EdDSAs", Chalkias et al., 2020)
+///
+/// s must always be canonical (s < L)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VerificationPolicy {
+    /// RFC 8032: A and R must be canonical encodings, and s * B = R + k * A
+    #[default]
+    Strict,
+    /// RFC 8032 with the cofactored equation [8] s * B = [8] R + [8] k * A, which also accepts
+    /// the signatures where s * B - R - k * A is a nonzero point of small order
+    Cofactored,
+    /// ZIP-215 (Zcash): the cofactored equation, and non-canonical encodings of A and R are
+    /// accepted, so that every node agrees on validity and signatures can be batch verified
+    Zip215,
+    /// `Strict`, and A must not be a point of small order, which any message can be signed for
+    NoSmallOrderKeys,
+}
+
+/// pure Ed25519 (RFC 8032, section 5.1), without domain separation
+pub fn verify(
+    public: &VerifyingKey,
+    message: &[u8],
+    signature: &Signature,
+    policy: VerificationPolicy,
+) -> bool {
+    verify_detailed(public, message, signature, policy).is_ok()
+}
+
+/// Ed25519ctx, the context must be the one given to `sign_with_context`
+pub fn verify_with_context(
+    public: &VerifyingKey,
+    context: &[u8],
+    message: &[u8],
+    signature: &Signature,
+    policy: VerificationPolicy,
+) -> bool {
+    verify_with_context_detailed(public, context, message, signature, policy).is_ok()
+}
+
+/// Ed25519ph, the context must be the one given to `sign_prehashed`
+pub fn verify_prehashed(
+    public: &VerifyingKey,
+    context: &[u8],
+    message: &[u8],
+    signature: &Signature,
+    policy: VerificationPolicy,
+) -> bool {
+    verify_prehashed_detailed(public, context, message, signature, policy).is_ok()
+}
+
+/// `verify`, which returns the first check that failed:
+/// - `NonCanonicalY` or `NonCanonicalX` if A or R is not canonical (except for `Zip215`)
+/// - `SmallOrder` if A has small order (only for `NoSmallOrderKeys`)
+/// - `EquationMismatch` if the verification equation of the policy does not hold
+///
+/// the other errors (`NotOnCurve`, `ScalarOutOfRange`) come from `VerifyingKey::from_bytes` and
+/// `Signature::from_bytes`
+pub fn verify_detailed(
+    public: &VerifyingKey,
+    message: &[u8],
+    signature: &Signature,
+    policy: VerificationPolicy,
+) -> Result<(), Error> {
+    verify_with_dom(public, &[], message, signature, policy)
+}
+
+/// `verify_with_context`, with the errors of `verify_detailed`, or `ContextTooLong`
+pub fn verify_with_context_detailed(
+    public: &VerifyingKey,
+    context: &[u8],
+    message: &[u8],
+    signature: &Signature,
+    policy: VerificationPolicy,
+) -> Result<(), Error> {
+    check_context(context)?;
+    verify_with_dom(public, &dom2(0, context), message, signature, policy)
+}
+
+/// `verify_prehashed`, with the errors of `verify_detailed`, or `ContextTooLong`
+pub fn verify_prehashed_detailed(
+    public: &VerifyingKey,
+    context: &[u8],
+    message: &[u8],
+    signature: &Signature,
+    policy: VerificationPolicy,
+) -> Result<(), Error> {
+    check_context(context)?;
+    let prehash = Sha512::digest(message);
+    verify_with_dom(public, &dom2(1, context), &prehash, signature, policy)
+}
+
+/// `verify_detailed` on a message read from a stream (e.g. a file), in one pass and constant
+/// memory
+///
+/// fails with `Io` if reading fails, or with the errors of `verify_detailed`
+pub fn verify_reader<R: Read>(
+    public: &VerifyingKey,
+    message: R,
+    signature: &Signature,
+    policy: VerificationPolicy,
+) -> Result<(), Error> {
+    verify_with_dom_reader(public, &[], message, signature, policy)
+}
+
+/// `verify_with_context_detailed` on a message read from a stream, see `verify_reader`
+pub fn verify_with_context_reader<R: Read>(
+    public: &VerifyingKey,
+    context: &[u8],
+    message: R,
+    signature: &Signature,
+    policy: VerificationPolicy,
+) -> Result<(), Error> {
+    check_context(context)?;
+    verify_with_dom_reader(public, &dom2(0, context), message, signature, policy)
+}
+
+/// `verify_prehashed_detailed` on a message read from a stream, see `verify_reader`
+pub fn verify_prehashed_reader<R: Read>(
+    public: &VerifyingKey,
+    context: &[u8],
+    message: R,
+    signature: &Signature,
+    policy: VerificationPolicy,
+) -> Result<(), Error> {
+    check_context(context)?;
+    let prehash = prehash_reader(message)?;
+    verify_with_dom_reader(public, &dom2(1, context), &prehash[..], signature, policy)
+}
+
+// dom is prepended to the hash, it is empty for pure Ed25519
+fn verify_with_dom(
+    public: &VerifyingKey,
+    dom: &[u8],
+    message: &[u8],
+    signature: &Signature,
+    policy: VerificationPolicy,
+) -> Result<(), Error> {
+    // reading a slice cannot fail, so no `Io` error comes from here
+    verify_with_dom_reader(public, dom, message, signature, policy)
+}
+
+// the checks which do not need the message come first, so that the message is not read for a
+// signature rejected anyway
+fn verify_with_dom_reader<R: Read>(
+    public: &VerifyingKey,
+    dom: &[u8],
+    mut message: R,
+    signature: &Signature,
+    policy: VerificationPolicy,
+) -> Result<(), Error> {
+    if policy != VerificationPolicy::Zip215 {
+        for (canonical, bytes) in [
+            (public.is_canonical(), public.as_bytes()),
+            (signature.is_canonical(), signature.r_bytes()),
+        ] {
+            if !canonical {
+                return Err(non_canonical_error(bytes));
+            }
         }
-        s
-    };
-    let Ok(a) = ProjEdPoint::decode(public) else {
-        return false;
-    };
+    }
+    if policy == VerificationPolicy::NoSmallOrderKeys && public.point().is_small_order() {
+        return Err(Error::SmallOrder);
+    }
 
-    let k = {
-        let mut bytes = signature[0..32].to_vec();
-        bytes.append(&mut public.to_vec());
-        bytes.append(&mut message.to_vec());
-        Integer::from_digits(Sha512::digest(&bytes).as_slice(), Order::Lsf)
+    let mut hasher = challenge_hasher(public, dom, signature);
+    update_from(&mut message, &mut [&mut hasher])?;
+    let k = Scalar::from_bytes_mod_order_wide(&hasher.finalize().into());
+    let holds = match policy {
+        VerificationPolicy::Strict | VerificationPolicy::NoSmallOrderKeys => {
+            holds(public, signature, &k)
+        }
+        VerificationPolicy::Cofactored | VerificationPolicy::Zip215 => {
+            holds_cofactored(public, signature, &k)
+        }
     };
+    holds.then_some(()).ok_or(Error::EquationMismatch)
+}
+
+// why the strict decoding rejects a non-canonical encoding (NonCanonicalY or NonCanonicalX)
+fn non_canonical_error(bytes: &Key) -> Error {
+    match ProjEdPoint::<Curve25519>::decode(*bytes) {
+        Err(e) => e,
+        Ok(_) => unreachable!("canonical encodings are decoded"),
+    }
+}
+
+// k = H(dom || R || A || M), always computed from the encodings of R and A in the signature
+// and the key
+fn challenge(public: &VerifyingKey, dom: &[u8], message: &[u8], signature: &Signature) -> Scalar {
+    let hasher = challenge_hasher(public, dom, signature).chain_update(message);
+    Scalar::from_bytes_mod_order_wide(&hasher.finalize().into())
+}
+
+// H(dom || R || A), to which the message is then fed
+fn challenge_hasher(public: &VerifyingKey, dom: &[u8], signature: &Signature) -> Sha512 {
+    Sha512::new()
+        .chain_update(dom)
+        .chain_update(signature.r_bytes())
+        .chain_update(public.as_bytes())
+}
+
+// s * B = R + k * A  <=>  R = k * (-A) + s * B
+// A is negated rather than k: (L - k) * A is not -k * A when A has a small-order component
+// the comparison does not branch on the coordinates, so it leaks nothing on where they differ
+fn holds(public: &VerifyingKey, signature: &Signature, k: &Scalar) -> bool {
+    ProjEdPoint::vartime_double_scalar_mul_basepoint(k, &-*public.point(), signature.s())
+        .ct_eq(signature.r())
+        .into()
+}
+
+// [8] (s * B - R - k * A) = 0
+fn holds_cofactored(public: &VerifyingKey, signature: &Signature, k: &Scalar) -> bool {
+    (ProjEdPoint::vartime_double_scalar_mul_basepoint(k, &-*public.point(), signature.s())
+        + &-*signature.r())
+        .mul_by_cofactor()
+        .is_identity()
+}
+
+/// verify many pure Ed25519 signatures at once, returns whether each of them is valid
+///
+/// the batch is accepted if [8] sum z_i (s_i * B - R_i - k_i * A_i) = 0 for random 128-bit z_i,
+/// which is a single multiscalar multiplication; otherwise each signature is checked on its own
+/// to find the bad ones, non-canonical keys and signatures are rejected without being part of
+/// the batch
+///
+/// the result is the one of `verify` with `VerificationPolicy::Cofactored` on each signature
+/// (except with probability about 2^-128): a random linear combination cannot tell apart the
+/// small-order components that the cofactorless equation of `VerificationPolicy::Strict` rejects
+pub fn verify_batch(items: &[(VerifyingKey, &[u8], Signature)]) -> Vec<bool> {
+    let challenges = items
+        .iter()
+        .map(|(public, message, signature)| {
+            (public.is_canonical() && signature.is_canonical())
+                .then(|| challenge(public, &[], message, signature))
+        })
+        .collect::<Vec<_>>();
 
-    get_b() * &s == r + &(a * &k)
+    // sum z_i s_i * B + sum (-z_i) * R_i + sum (-z_i k_i) * A_i
+    let mut rng = rand::thread_rng();
+    let mut b_scalar = Scalar::ZERO;
+    let mut scalars = vec![];
+    let mut points = vec![];
+    for ((public, _, signature), k) in items.iter().zip(&challenges) {
+        let Some(k) = k else {
+            continue;
+        };
+        let mut z_bytes = [0u8; 32];
+        rng.fill_bytes(&mut z_bytes[..16]);
+        let z = Scalar::from_bytes_mod_order(z_bytes);
+        b_scalar = b_scalar + z * *signature.s();
+        scalars.extend([-z, -(z * *k)]);
+        points.extend([*signature.r(), *public.point()]);
+    }
+    scalars.push(b_scalar);
+    points.push(ProjEdPoint::basepoint());
+
+    if ProjEdPoint::vartime_multiscalar_mul(&scalars, &points)
+        .mul_by_cofactor()
+        .is_identity()
+    {
+        return challenges.iter().map(Option::is_some).collect();
+    }
+
+    items
+        .iter()
+        .zip(&challenges)
+        .map(|((public, _, signature), k)| {
+            k.is_some_and(|k| holds_cofactored(public, signature, &k))
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -42,18 +295,46 @@ mod tests {
             .collect::<Vec<u8>>()
     }
 
+    // a key or signature which cannot be decoded is rejected
+    fn check(
+        public: &Key,
+        message: &[u8],
+        signature: &[u8; 64],
+        policy: VerificationPolicy,
+    ) -> bool {
+        check_detailed(public, message, signature, policy).is_ok()
+    }
+
+    fn check_detailed(
+        public: &Key,
+        message: &[u8],
+        signature: &[u8; 64],
+        policy: VerificationPolicy,
+    ) -> Result<(), Error> {
+        let public = VerifyingKey::from_bytes(public)?;
+        let signature = Signature::from_bytes(signature)?;
+        verify_detailed(&public, message, &signature, policy)
+    }
+
     fn correct_verify(public: &str, message: &str, signature: &str) {
-        let public = bytes_from_str(public).try_into().unwrap();
+        let public = VerifyingKey::try_from(&bytes_from_str(public)[..]).unwrap();
         let message = bytes_from_str(message);
-        let signature = bytes_from_str(signature).try_into().unwrap();
-        assert!(verify(public, &message, signature));
+        let signature = Signature::try_from(&bytes_from_str(signature)[..]).unwrap();
+        assert!(verify(
+            &public,
+            &message,
+            &signature,
+            VerificationPolicy::Strict
+        ));
     }
 
     fn incorrect_verify(public: &str, message: &str, signature: &str) {
-        let public = bytes_from_str(public).try_into().unwrap();
-        let message = bytes_from_str(message);
-        let signature = bytes_from_str(signature).try_into().unwrap();
-        assert!(!verify(public, &message, signature));
+        assert!(!check(
+            &bytes_from_str(public).try_into().unwrap(),
+            &bytes_from_str(message),
+            &bytes_from_str(signature).try_into().unwrap(),
+            VerificationPolicy::Strict
+        ));
     }
 
     #[test]
@@ -103,4 +384,414 @@ mod tests {
         let signature = "dc2a4459e7369633a52b1bf287839a00201009a3efbf3ecb69bea2186c26b58909351fc9ac90b3ecfdfbc7c66431e0303dca179c138ac17ad9bef1177331a704";
         incorrect_verify(public, message, signature);
     }
+
+    // RFC 8032, section 7.2
+    #[test]
+    fn correct_verify_ctx() {
+        let public = VerifyingKey::try_from(
+            &bytes_from_str("dfc9425e4f968f7f0c29f0259cf5f9aed6851c2bb4ad8bfb860cfee0ab248292")[..],
+        )
+        .unwrap();
+        let message = bytes_from_str("f726936d19c800494e3fdaff20b276a8");
+        let signature = Signature::try_from(&bytes_from_str("55a4cc2f70a54e04288c5f4cd1e45a7bb520b36292911876cada7323198dd87a8b36950b95130022907a7fb7c4e9b2d5f6cca685a587b4b21f4b888e4e7edb0d")[..]).unwrap();
+        assert!(verify_with_context(
+            &public,
+            b"foo",
+            &message,
+            &signature,
+            VerificationPolicy::Strict
+        ));
+
+        // the context separates the domains: another context, Ed25519ph or pure Ed25519 reject
+        assert!(!verify_with_context(
+            &public,
+            b"bar",
+            &message,
+            &signature,
+            VerificationPolicy::Strict
+        ));
+        assert!(!verify_prehashed(
+            &public,
+            b"foo",
+            &message,
+            &signature,
+            VerificationPolicy::Strict
+        ));
+        assert!(!verify(
+            &public,
+            &message,
+            &signature,
+            VerificationPolicy::Strict
+        ));
+    }
+
+    #[test]
+    fn correct_verify_reader() {
+        let private = crate::lib::keys::SigningKey::generate().expand();
+        let public = private.verifying_key();
+        // longer than the buffer of update_from
+        let message = (0..200_000u32).map(|i| i as u8).collect::<Vec<u8>>();
+        let policy = VerificationPolicy::Strict;
+
+        let signature = private.sign(&message);
+        assert_eq!(
+            verify_reader(&public, &message[..], &signature, policy),
+            Ok(())
+        );
+        assert_eq!(
+            verify_reader(&public, &message[1..], &signature, policy),
+            Err(Error::EquationMismatch)
+        );
+
+        let signature = private.sign_with_context(b"foo", &message).unwrap();
+        assert_eq!(
+            verify_with_context_reader(&public, b"foo", &message[..], &signature, policy),
+            Ok(())
+        );
+        assert_eq!(
+            verify_with_context_reader(&public, b"bar", &message[..], &signature, policy),
+            Err(Error::EquationMismatch)
+        );
+
+        let signature = private.sign_prehashed(b"foo", &message).unwrap();
+        assert_eq!(
+            verify_prehashed_reader(&public, b"foo", &message[..], &signature, policy),
+            Ok(())
+        );
+        assert_eq!(
+            verify_prehashed_reader(&public, &[0u8; 256], &message[..], &signature, policy),
+            Err(Error::ContextTooLong)
+        );
+    }
+
+    // RFC 8032, section 7.3
+    #[test]
+    fn correct_verify_ph() {
+        let public = VerifyingKey::try_from(
+            &bytes_from_str("ec172b93ad5e563bf4932c70e1245034c35467ef2efd4d64ebf819683467e2bf")[..],
+        )
+        .unwrap();
+        let signature = Signature::try_from(&bytes_from_str("98a70222f0b8121aa9d30f813d683f809e462b469c7ff87639499bb94e6dae4131f85042463c2a355a2003d062adf5aaa10b8c61e636062aaad11c2a26083406")[..]).unwrap();
+        assert!(verify_prehashed(
+            &public,
+            &[],
+            b"abc",
+            &signature,
+            VerificationPolicy::Strict
+        ));
+
+        assert!(!verify_prehashed(
+            &public,
+            &[],
+            b"abd",
+            &signature,
+            VerificationPolicy::Strict
+        ));
+        assert!(!verify_with_context(
+            &public,
+            &[],
+            b"abc",
+            &signature,
+            VerificationPolicy::Strict
+        ));
+        assert!(!verify(
+            &public,
+            b"abc",
+            &signature,
+            VerificationPolicy::Strict
+        ));
+    }
+
+    fn rfc_items() -> Vec<(Key, Vec<u8>, [u8; 64])> {
+        [
+            ("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a", "", "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b"),
+            ("3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c", "72", "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00"),
+            ("fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025", "af82", "6291d657deec24024827e69c3abe01a30ce548a284743a445e3680d7db5ac3ac18ff9b538d16f290ae67f760984dc6594a7c15e9716ed28dc027beceea1ec40a"),
+        ]
+        .iter()
+        .map(|(public, message, signature)| {
+            (
+                bytes_from_str(public).try_into().unwrap(),
+                bytes_from_str(message),
+                bytes_from_str(signature).try_into().unwrap(),
+            )
+        })
+        .collect()
+    }
+
+    // as `check`, items which cannot be decoded are rejected
+    fn batch(items: &[(Key, Vec<u8>, [u8; 64])]) -> Vec<bool> {
+        let decoded = items
+            .iter()
+            .map(|(public, message, signature)| {
+                match (
+                    VerifyingKey::from_bytes(public),
+                    Signature::from_bytes(signature),
+                ) {
+                    (Ok(public), Ok(signature)) => Some((public, &message[..], signature)),
+                    _ => None,
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut results =
+            verify_batch(&decoded.iter().flatten().copied().collect::<Vec<_>>()).into_iter();
+        decoded
+            .iter()
+            .map(|item| item.is_some() && results.next().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn correct_verify_batch() {
+        assert_eq!(batch(&rfc_items()), vec![true; 3]);
+        assert_eq!(batch(&[]), Vec::<bool>::new());
+
+        // enough signatures to go through Pippenger's method
+        let mut items = vec![];
+        for i in 0..200u32 {
+            let (private, public) = crate::lib::keygen::generate_key_pair();
+            let message = i.to_le_bytes().to_vec();
+            let signature = crate::lib::sign::sign(&private, &message);
+            items.push((public.to_bytes(), message, signature.to_bytes()));
+        }
+        assert_eq!(batch(&items), vec![true; 200]);
+    }
+
+    #[test]
+    fn incorrect_verify_batch() {
+        let mut items = rfc_items();
+        // wrong message, non-canonical s, invalid R
+        items[0].1 = vec![0];
+        let mut expected = vec![false, true, true];
+        assert_eq!(batch(&items), expected);
+
+        items[1].2[63] = 0xff;
+        expected[1] = false;
+        assert_eq!(batch(&items), expected);
+
+        items[2].2[..32].copy_from_slice(&[0xff; 32]);
+        expected[2] = false;
+        assert_eq!(batch(&items), expected);
+    }
+
+    #[test]
+    fn batch_matches_cofactored() {
+        use crate::lib::arithmetic::proj_edwards::EIGHT_TORSION;
+        use crate::lib::keygen::gen_public_scalar_prefix;
+
+        // R = r * B + T with T of order 8 and s = r + k * a: only the cofactored equation holds
+        let (public, a, _) = gen_public_scalar_prefix(&[7u8; 32]);
+        let message = b"torsion";
+        let r = Scalar::from(1234);
+        let mut r_point = ProjEdPoint::mul_base(&r) + &EIGHT_TORSION[1];
+        let r_encoding = r_point.encode();
+        let mut bytes = r_encoding.to_vec();
+        bytes.extend_from_slice(&public);
+        bytes.extend_from_slice(message);
+        let k = Scalar::from_bytes_mod_order_wide(&Sha512::digest(&bytes).into());
+        let mut signature = [0u8; 64];
+        signature[..32].copy_from_slice(&r_encoding);
+        signature[32..].copy_from_slice(&(r + k * *a).to_bytes());
+
+        assert!(!check(
+            &public,
+            message,
+            &signature,
+            VerificationPolicy::Strict
+        ));
+        assert!(check(
+            &public,
+            message,
+            &signature,
+            VerificationPolicy::Cofactored
+        ));
+
+        let mut items = rfc_items();
+        items.push((public, message.to_vec(), signature));
+        assert_eq!(batch(&items), vec![true; 4]);
+    }
+
+    // "Taming the many EdDSAs" (Chalkias et al., 2020), test vectors 0 to 11 as (message,
+    // public key, signature)
+    //
+    // 0-2: small-order A and/or R, 3: mixed-order A and R, 4-5: only the cofactored equation
+    // holds, 6-7: s >= L, 8-9: non-canonical R, 10-11: non-canonical A
+    const TAMING: [(&str, &str, &str); 12] = [
+        (
+            "8c93255d71dcab10e8f379c26200f3c7bd5f09d9bc3068d3ef4edeb4853022b6",
+            "c7176a703d4dd84fba3c0b760d10670f2a2053fa2c39ccc64ec7fd7792ac03fa",
+            "c7176a703d4dd84fba3c0b760d10670f2a2053fa2c39ccc64ec7fd7792ac037a0000000000000000000000000000000000000000000000000000000000000000",
+        ),
+        (
+            "9bd9f44f4dcc75bd531b56b2cd280b0bb38fc1cd6d1230e14861d861de092e79",
+            "c7176a703d4dd84fba3c0b760d10670f2a2053fa2c39ccc64ec7fd7792ac03fa",
+            "f7badec5b8abeaf699583992219b7b223f1df3fbbea919844e3f7c554a43dd43a5bb704786be79fc476f91d3f3f89b03984d8068dcf1bb7dfc6637b45450ac04",
+        ),
+        (
+            "aebf3f2601a0c8c5d39cc7d8911642f740b78168218da8471772b35f9d35b9ab",
+            "f7badec5b8abeaf699583992219b7b223f1df3fbbea919844e3f7c554a43dd43",
+            "c7176a703d4dd84fba3c0b760d10670f2a2053fa2c39ccc64ec7fd7792ac03fa8c4bd45aecaca5b24fb97bc10ac27ac8751a7dfe1baff8b953ec9f5833ca260e",
+        ),
+        (
+            "9bd9f44f4dcc75bd531b56b2cd280b0bb38fc1cd6d1230e14861d861de092e79",
+            "cdb267ce40c5cd45306fa5d2f29731459387dbf9eb933b7bd5aed9a765b88d4d",
+            "9046a64750444938de19f227bb80485e92b83fdb4b6506c160484c016cc1852f87909e14428a7a1d62e9f22f3d3ad7802db02eb2e688b6c52fcd6648a98bd009",
+        ),
+        (
+            "e47d62c63f830dc7a6851a0b1f33ae4bb2f507fb6cffec4011eaccd55b53f56c",
+            "cdb267ce40c5cd45306fa5d2f29731459387dbf9eb933b7bd5aed9a765b88d4d",
+            "160a1cb0dc9c0258cd0a7d23e94d8fa878bcb1925f2c64246b2dee1796bed5125ec6bc982a269b723e0668e540911a9a6a58921d6925e434ab10aa7940551a09",
+        ),
+        (
+            "e47d62c63f830dc7a6851a0b1f33ae4bb2f507fb6cffec4011eaccd55b53f56c",
+            "cdb267ce40c5cd45306fa5d2f29731459387dbf9eb933b7bd5aed9a765b88d4d",
+            "21122a84e0b5fca4052f5b1235c80a537878b38f3142356b2c2384ebad4668b7e40bc836dac0f71076f9abe3a53f9c03c1ceeeddb658d0030494ace586687405",
+        ),
+        (
+            "85e241a07d148b41e47d62c63f830dc7a6851a0b1f33ae4bb2f507fb6cffec40",
+            "442aad9f089ad9e14647b1ef9099a1ff4798d78589e66f28eca69c11f582a623",
+            "e96f66be976d82e60150baecff9906684aebb1ef181f67a7189ac78ea23b6c0e547f7690a0e2ddcd04d87dbc3490dc19b3b3052f7ff0538cb68afb369ba3a514",
+        ),
+        (
+            "85e241a07d148b41e47d62c63f830dc7a6851a0b1f33ae4bb2f507fb6cffec40",
+            "442aad9f089ad9e14647b1ef9099a1ff4798d78589e66f28eca69c11f582a623",
+            "8ce5b96c8f26d0ab6c47958c9e68b937104cd36e13c33566acd2fe8d38aa19427e71f98a473474f2f13f06f97c20d58cc3f54b8bd0d272f42b695dd7e89a8c22",
+        ),
+        (
+            "9bedc267423725d473888631ebf45988bad3db83851ee85c85e241a07d148b41",
+            "f7badec5b8abeaf699583992219b7b223f1df3fbbea919844e3f7c554a43dd43",
+            "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff03be9678ac102edcd92b0210bb34d7428d12ffc5df5f37e359941266a4e35f0f",
+        ),
+        (
+            "9bedc267423725d473888631ebf45988bad3db83851ee85c85e241a07d148b41",
+            "f7badec5b8abeaf699583992219b7b223f1df3fbbea919844e3f7c554a43dd43",
+            "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffca8c5b64cd208982aa38d4936621a4775aa233aa0505711d8fdcfdaa943d4908",
+        ),
+        (
+            "e96b7021eb39c1a163b6da4e3093dcd3f21387da4cc4572be588fafae23c155b",
+            "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            "a9d55260f765261eb9b84e106f665e00b867287a761990d7135963ee0a7d59dca5bb704786be79fc476f91d3f3f89b03984d8068dcf1bb7dfc6637b45450ac04",
+        ),
+        (
+            "39a591f5321bbe07fd5a23dc2f39d025d74526615746727ceefd6e82ae65c06f",
+            "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            "a9d55260f765261eb9b84e106f665e00b867287a761990d7135963ee0a7d59dca5bb704786be79fc476f91d3f3f89b03984d8068dcf1bb7dfc6637b45450ac04",
+        ),
+    ];
+
+    fn taming(policy: VerificationPolicy) -> Vec<bool> {
+        TAMING
+            .iter()
+            .map(|(message, public, signature)| {
+                check(
+                    &bytes_from_str(public).try_into().unwrap(),
+                    &bytes_from_str(message),
+                    &bytes_from_str(signature).try_into().unwrap(),
+                    policy,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn taming_strict() {
+        let expected = [1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0].map(|b| b == 1);
+        assert_eq!(taming(VerificationPolicy::Strict), expected);
+    }
+
+    #[test]
+    fn taming_cofactored() {
+        let expected = [1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0].map(|b| b == 1);
+        assert_eq!(taming(VerificationPolicy::Cofactored), expected);
+    }
+
+    #[test]
+    fn taming_zip215() {
+        // 8 is rejected: R is read as the point of order 2, for which no equation holds
+        let expected = [1, 1, 1, 1, 1, 1, 0, 0, 0, 1, 1, 1].map(|b| b == 1);
+        assert_eq!(taming(VerificationPolicy::Zip215), expected);
+    }
+
+    #[test]
+    fn taming_no_small_order_keys() {
+        let expected = [0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0].map(|b| b == 1);
+        assert_eq!(taming(VerificationPolicy::NoSmallOrderKeys), expected);
+    }
+
+    #[test]
+    fn taming_detailed() {
+        let errors = |policy| {
+            TAMING
+                .iter()
+                .map(|(message, public, signature)| {
+                    check_detailed(
+                        &bytes_from_str(public).try_into().unwrap(),
+                        &bytes_from_str(message),
+                        &bytes_from_str(signature).try_into().unwrap(),
+                        policy,
+                    )
+                    .err()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mismatch = Some(Error::EquationMismatch);
+        let out_of_range = Some(Error::ScalarOutOfRange);
+        // R (8-9) or A (10-11) is (0, -1) encoded with the sign bit set
+        let negative_zero = Some(Error::NonCanonicalX);
+        assert_eq!(
+            errors(VerificationPolicy::Strict),
+            [
+                None,
+                None,
+                None,
+                None,
+                mismatch,
+                mismatch,
+                out_of_range,
+                out_of_range,
+                negative_zero,
+                negative_zero,
+                negative_zero,
+                negative_zero,
+            ]
+        );
+
+        let small_order = Some(Error::SmallOrder);
+        assert_eq!(
+            errors(VerificationPolicy::NoSmallOrderKeys)[..4],
+            [small_order, small_order, None, None]
+        );
+        assert_eq!(errors(VerificationPolicy::Zip215)[8], mismatch);
+    }
+
+    #[test]
+    fn reject_detailed() {
+        let private = crate::lib::keys::SigningKey::from_bytes(&[7; 32]);
+        let public = private.verifying_key();
+        let signature = crate::lib::sign::sign(&private, b"message");
+        let policy = VerificationPolicy::Strict;
+
+        // y = 2 is not the y-coordinate of a point, y = p is not canonical
+        let mut y = [0u8; 32];
+        y[0] = 2;
+        let mut p = [0xff; 32];
+        p[0] = 0xed;
+        p[31] = 0x7f;
+        for (bytes, error) in [(y, Error::NotOnCurve), (p, Error::NonCanonicalY)] {
+            assert_eq!(
+                check_detailed(&bytes, b"message", &signature.to_bytes(), policy),
+                Err(error)
+            );
+        }
+
+        assert_eq!(
+            verify_with_context_detailed(&public, &[0; 256], b"message", &signature, policy),
+            Err(Error::ContextTooLong)
+        );
+        assert_eq!(
+            verify_detailed(&public, b"message", &signature, policy),
+            Ok(())
+        );
+    }
 }
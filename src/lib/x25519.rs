@@ -0,0 +1,234 @@
+use super::arithmetic::curve::Curve25519;
+use super::arithmetic::field::FieldElement;
+use super::arithmetic::montgomery::MPoint;
+use super::arithmetic::proj_edwards::ProjEdPoint;
+use super::arithmetic::xline_proj_montgomery::ladder;
+use super::keygen::{expand_private, prune};
+use super::{Error, Key};
+use rand::RngCore;
+use zeroize::Zeroizing;
+
+/// u-coordinate of the base point of curve25519
+pub const BASEPOINT: Key = [
+    9, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+];
+
+/// X25519 function from RFC 7748 (section 5): clamp the scalar and multiply the point of
+/// u-coordinate u by it, with the Montgomery ladder
+pub fn x25519(scalar: &Key, u: Key) -> Key {
+    let mut scalar = Zeroizing::new(*scalar);
+    prune(&mut scalar);
+
+    // from_bytes ignores the most significant bit and accepts non-canonical values, as required
+    let u = FieldElement::from_bytes(&u);
+    let (result, _) = ladder::<Curve25519>(&scalar, &u);
+    result.to_bytes()
+}
+
+pub fn gen_public(private: &Key) -> Key {
+    x25519(private, BASEPOINT)
+}
+
+/// returns (private, public), the private key is wiped on drop
+pub fn generate_key_pair() -> (Zeroizing<Key>, Key) {
+    let mut private = Zeroizing::new([0u8; 32]);
+    rand::thread_rng().fill_bytes(private.as_mut());
+
+    let public = gen_public(&private);
+    (private, public)
+}
+
+/// shared secret of the Diffie-Hellman key exchange, fails with `SmallOrder` if it is all-zero,
+/// i.e. if the peer public key is a point of small order
+///
+/// the shared secret is wiped on drop
+pub fn shared_secret(private: &Key, peer_public: Key) -> Result<Zeroizing<Key>, Error> {
+    let shared = Zeroizing::new(x25519(private, peer_public));
+
+    // constant time check that all the bytes are 0
+    if shared.iter().fold(0, |acc, byte| acc | byte) == 0 {
+        Err(Error::SmallOrder)
+    } else {
+        Ok(shared)
+    }
+}
+
+/// X25519 public key (u-coordinate) of the point of an Ed25519 public key, like
+/// `crypto_sign_ed25519_pk_to_curve25519` from libsodium
+///
+/// fails if the key is not a valid encoding, has small order (`SmallOrder`) or is not in the
+/// prime-order subgroup (`NotTorsionFree`)
+pub fn public_from_ed25519(public: Key) -> Result<Key, Error> {
+    let point = ProjEdPoint::decode(public)?;
+    if point.is_small_order() {
+        return Err(Error::SmallOrder);
+    }
+    if !point.is_torsion_free() {
+        return Err(Error::NotTorsionFree);
+    }
+    // u = (1 + y) / (1 - y)
+    Ok(MPoint::try_from(&point)?.x().to_bytes())
+}
+
+/// X25519 secret key from an Ed25519 private key (seed), like
+/// `crypto_sign_ed25519_sk_to_curve25519` from libsodium
+///
+/// this is the pruned scalar of the Ed25519 key, which X25519 clamping leaves unchanged, so that
+/// `gen_public(private_from_ed25519(private))` is the conversion of the Ed25519 public key
+pub fn private_from_ed25519(private: &Key) -> Zeroizing<Key> {
+    let (scalar, _) = expand_private(private);
+    scalar
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lib::arithmetic::proj_edwards::EIGHT_TORSION;
+    use crate::lib::arithmetic::scalar::Scalar;
+
+    fn key_from_str(s: &str) -> Key {
+        s.chars()
+            .collect::<Vec<char>>()
+            .chunks(2)
+            .map(|chunk| chunk.iter().collect::<String>())
+            .map(|byte| u8::from_str_radix(&byte, 16).unwrap())
+            .collect::<Vec<u8>>()
+            .try_into()
+            .unwrap()
+    }
+
+    // RFC 7748, section 5.2
+    fn correct_iterated(iterations: usize, expected: &str) {
+        let mut k = BASEPOINT;
+        let mut u = BASEPOINT;
+        for _ in 0..iterations {
+            (k, u) = (x25519(&k, u), k);
+        }
+        assert_eq!(k, key_from_str(expected));
+    }
+
+    #[test]
+    fn correct_iterated_1() {
+        correct_iterated(
+            1,
+            "422c8e7a6227d7bca1350b3e2bb7279f7897b87bb6854b783c60e80311ae3079",
+        );
+    }
+
+    #[test]
+    fn correct_iterated_1000() {
+        correct_iterated(
+            1_000,
+            "684cf59ba83309552800ef566f2f4d3c1c3887c49360e3875f2eb94d99532c51",
+        );
+    }
+
+    // takes a few minutes, run with `cargo test --release -- --ignored`
+    #[test]
+    #[ignore]
+    fn correct_iterated_1000000() {
+        correct_iterated(
+            1_000_000,
+            "7c3911e0ab2586fd864497297e575e6f3bc601c0883c30df5f4dd2d24f2a6fc7",
+        );
+    }
+
+    #[test]
+    fn correct_x25519() {
+        assert_eq!(
+            x25519(
+                &key_from_str("a546e36bf0527c9d3b16154b82465edd62144c0ac1fc5a18506a2244ba449ac4"),
+                key_from_str("e6db6867583030db3594c1a424b15f7c726624ec26b3353b10a903a6d0ab1c4c")
+            ),
+            key_from_str("c3da55379de9c6908e94ea4df28d084f32eccf03491c71f754b4075577a28552")
+        );
+    }
+
+    // RFC 7748, section 6.1
+    #[test]
+    fn correct_diffie_hellman() {
+        let alice_private =
+            key_from_str("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a");
+        let bob_private =
+            key_from_str("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb");
+        let alice_public = gen_public(&alice_private);
+        let bob_public = gen_public(&bob_private);
+        assert_eq!(
+            alice_public,
+            key_from_str("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a")
+        );
+        assert_eq!(
+            bob_public,
+            key_from_str("de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f")
+        );
+
+        let expected = Zeroizing::new(key_from_str(
+            "4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742",
+        ));
+        assert_eq!(
+            shared_secret(&alice_private, bob_public),
+            Ok(expected.clone())
+        );
+        assert_eq!(shared_secret(&bob_private, alice_public), Ok(expected));
+    }
+
+    #[test]
+    fn reject_small_order_public() {
+        let (private, _) = generate_key_pair();
+        // u = 0 and u = 1 are points of order 2 and 4
+        let mut one = [0u8; 32];
+        one[0] = 1;
+        assert_eq!(shared_secret(&private, [0u8; 32]), Err(Error::SmallOrder));
+        assert_eq!(shared_secret(&private, one), Err(Error::SmallOrder));
+    }
+
+    // from the tests of libsodium (ed25519_convert)
+    #[test]
+    fn correct_from_ed25519() {
+        let ed_private =
+            key_from_str("421151a459faeade3d247115f94aedae42318124095afabe4d1451a559faedee");
+        let ed_public =
+            key_from_str("b5076a8474a832daee4dd5b4040983b6623b5f344aca57d4d6ee4baf3f259e6e");
+        let private = private_from_ed25519(&ed_private);
+        let public = public_from_ed25519(ed_public).unwrap();
+        assert_eq!(
+            *private,
+            key_from_str("8052030376d47112be7f73ed7a019293dd12ad910b654455798b4667d73de166")
+        );
+        assert_eq!(
+            public,
+            key_from_str("f1814f0e8ff1043d8a44d25babff3cedcae6c22c3edaa48f857ae70de2baae50")
+        );
+        assert_eq!(gen_public(&private), public);
+    }
+
+    #[test]
+    fn correct_from_ed25519_key_pair() {
+        let (ed_private, ed_public) = crate::lib::keygen::generate_key_pair();
+        let private = private_from_ed25519(ed_private.as_bytes());
+        assert_eq!(
+            Ok(gen_public(&private)),
+            public_from_ed25519(ed_public.to_bytes())
+        );
+    }
+
+    #[test]
+    fn reject_from_ed25519() {
+        // identity, point of order 8, non-canonical y = p
+        for public in [
+            "0100000000000000000000000000000000000000000000000000000000000000",
+            "c7176a703d4dd84fba3c0b760d10670f2a2053fa2c39ccc64ec7fd7792ac037a",
+            "edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        ] {
+            assert!(public_from_ed25519(key_from_str(public)).is_err());
+        }
+
+        // base point plus a point of order 8
+        let mut b = ProjEdPoint::mul_base(&Scalar::ONE);
+        let mixed = (b + &EIGHT_TORSION[1]).encode();
+        assert!(public_from_ed25519(mixed).is_err());
+
+        // the base points correspond
+        assert_eq!(public_from_ed25519(b.encode()), Ok(BASEPOINT));
+    }
+}
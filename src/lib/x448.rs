@@ -0,0 +1,179 @@
+use super::arithmetic::field448::FieldElement448;
+use super::arithmetic::xline_proj_montgomery::ladder_bits;
+use super::{Error, X448Key};
+use rand::RngCore;
+use zeroize::Zeroizing;
+
+/// u-coordinate of the base point of curve448
+pub const BASEPOINT: X448Key = {
+    let mut u = [0u8; 56];
+    u[0] = 5;
+    u
+};
+
+/// (A + 2) / 4 for A = 156326
+const A24: u64 = 39_082;
+
+/// clear the two least significant bits and set the most significant one (RFC 7748, section 5)
+fn clamp(scalar: &mut X448Key) {
+    scalar[0] &= 0b1111_1100;
+    scalar[55] |= 0b1000_0000;
+}
+
+/// X448 function from RFC 7748 (section 5): clamp the scalar and multiply the point of
+/// u-coordinate u by it, with the Montgomery ladder over GF(2^448 - 2^224 - 1)
+pub fn x448(scalar: &X448Key, u: X448Key) -> X448Key {
+    let mut scalar = Zeroizing::new(*scalar);
+    clamp(&mut scalar);
+
+    // from_bytes accepts non-canonical values, as required
+    let u = FieldElement448::from_bytes(&u);
+    let (result, _) = ladder_bits(&FieldElement448::from(A24), scalar.as_ref(), 448, &u);
+    result.to_bytes()
+}
+
+pub fn gen_public(private: &X448Key) -> X448Key {
+    x448(private, BASEPOINT)
+}
+
+/// returns (private, public), the private key is wiped on drop
+pub fn generate_key_pair() -> (Zeroizing<X448Key>, X448Key) {
+    let mut private = Zeroizing::new([0u8; 56]);
+    rand::thread_rng().fill_bytes(private.as_mut());
+
+    let public = gen_public(&private);
+    (private, public)
+}
+
+/// shared secret of the Diffie-Hellman key exchange, fails with `SmallOrder` if it is all-zero,
+/// i.e. if the peer public key is a point of small order
+///
+/// the shared secret is wiped on drop
+pub fn shared_secret(private: &X448Key, peer_public: X448Key) -> Result<Zeroizing<X448Key>, Error> {
+    let shared = Zeroizing::new(x448(private, peer_public));
+
+    // constant time check that all the bytes are 0
+    if shared.iter().fold(0, |acc, byte| acc | byte) == 0 {
+        Err(Error::SmallOrder)
+    } else {
+        Ok(shared)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn key_from_str(s: &str) -> X448Key {
+        s.chars()
+            .collect::<Vec<char>>()
+            .chunks(2)
+            .map(|chunk| chunk.iter().collect::<String>())
+            .map(|byte| u8::from_str_radix(&byte, 16).unwrap())
+            .collect::<Vec<u8>>()
+            .try_into()
+            .unwrap()
+    }
+
+    // RFC 7748, section 5.2
+    fn correct_iterated(iterations: usize, expected: &str) {
+        let mut k = BASEPOINT;
+        let mut u = BASEPOINT;
+        for _ in 0..iterations {
+            (k, u) = (x448(&k, u), k);
+        }
+        assert_eq!(k, key_from_str(expected));
+    }
+
+    #[test]
+    fn correct_iterated_1() {
+        correct_iterated(
+            1,
+            "3f482c8a9f19b01e6c46ee9711d9dc14fd4bf67af30765c2ae2b846a4d23a8cd0db897086239492caf350b51f833868b9bc2b3bca9cf4113",
+        );
+    }
+
+    #[test]
+    fn correct_iterated_1000() {
+        correct_iterated(
+            1_000,
+            "aa3b4749d55b9daf1e5b00288826c467274ce3ebbdd5c17b975e09d4af6c67cf10d087202db88286e2b79fceea3ec353ef54faa26e219f38",
+        );
+    }
+
+    // takes a few minutes, run with `cargo test --release -- --ignored`
+    #[test]
+    #[ignore]
+    fn correct_iterated_1000000() {
+        correct_iterated(
+            1_000_000,
+            "077f453681caca3693198420bbe515cae0002472519b3e67661a7e89cab94695c8f4bcd66e61b9b9c946da8d524de3d69bd9d9d66b997e37",
+        );
+    }
+
+    #[test]
+    fn correct_x448_1() {
+        assert_eq!(
+            x448(
+                &key_from_str("3d262fddf9ec8e88495266fea19a34d28882acef045104d0d1aae121700a779c984c24f8cdd78fbff44943eba368f54b29259a4f1c600ad3"),
+                key_from_str("06fce640fa3487bfda5f6cf2d5263f8aad88334cbd07437f020f08f9814dc031ddbdc38c19c6da2583fa5429db94ada18aa7a7fb4ef8a086")
+            ),
+            key_from_str("ce3e4ff95a60dc6697da1db1d85e6afbdf79b50a2412d7546d5f239fe14fbaadeb445fc66a01b0779d98223961111e21766282f73dd96b6f")
+        );
+    }
+
+    #[test]
+    fn correct_x448_2() {
+        assert_eq!(
+            x448(
+                &key_from_str("203d494428b8399352665ddca42f9de8fef600908e0d461cb021f8c538345dd77c3e4806e25f46d3315c44e0a5b4371282dd2c8d5be3095f"),
+                key_from_str("0fbcc2f993cd56d3305b0b7d9e55d4c1a8fb5dbb52f8e9a1e9b6201b165d015894e56c4d3570bee52fe205e28a78b91cdfbde71ce8d157db")
+            ),
+            key_from_str("884a02576239ff7a2f2f63b2db6a9ff37047ac13568e1e30fe63c4a7ad1b3ee3a5700df34321d62077e63633c575c1c954514e99da7c179d")
+        );
+    }
+
+    // RFC 7748, section 6.2
+    #[test]
+    fn correct_diffie_hellman() {
+        let alice_private = key_from_str("9a8f4925d1519f5775cf46b04b5800d4ee9ee8bae8bc5565d498c28dd9c9baf574a9419744897391006382a6f127ab1d9ac2d8c0a598726b");
+        let bob_private = key_from_str("1c306a7ac2a0e2e0990b294470cba339e6453772b075811d8fad0d1d6927c120bb5ee8972b0d3e21374c9c921b09d1b0366f10b65173992d");
+        let alice_public = gen_public(&alice_private);
+        let bob_public = gen_public(&bob_private);
+        assert_eq!(
+            alice_public,
+            key_from_str("9b08f7cc31b7e3e67d22d5aea121074a273bd2b83de09c63faa73d2c22c5d9bbc836647241d953d40c5b12da88120d53177f80e532c41fa0")
+        );
+        assert_eq!(
+            bob_public,
+            key_from_str("3eb7a829b0cd20f5bcfc0b599b6feccf6da4627107bdb0d4f345b43027d8b972fc3e34fb4232a13ca706dcb57aec3dae07bdc1c67bf33609")
+        );
+
+        let expected = Zeroizing::new(key_from_str("07fff4181ac6cc95ec1c16a94a0f74d12da232ce40a77552281d282bb60c0b56fd2464c335543936521c24403085d59a449a5037514a879d"));
+        assert_eq!(
+            shared_secret(&alice_private, bob_public),
+            Ok(expected.clone())
+        );
+        assert_eq!(shared_secret(&bob_private, alice_public), Ok(expected));
+    }
+
+    #[test]
+    fn correct_key_pair() {
+        let (alice_private, alice_public) = generate_key_pair();
+        let (bob_private, bob_public) = generate_key_pair();
+        assert_eq!(
+            shared_secret(&alice_private, bob_public),
+            shared_secret(&bob_private, alice_public)
+        );
+    }
+
+    #[test]
+    fn reject_small_order_public() {
+        let (private, _) = generate_key_pair();
+        // u = 0 and u = 1 are points of order 2 and 4
+        let mut one = [0u8; 56];
+        one[0] = 1;
+        assert_eq!(shared_secret(&private, [0u8; 56]), Err(Error::SmallOrder));
+        assert_eq!(shared_secret(&private, one), Err(Error::SmallOrder));
+    }
+}
@@ -21,6 +21,43 @@ fn correct_sign() -> TestResult {
     Ok(())
 }
 
+#[test]
+fn correct_sign_mlock() -> TestResult {
+    let mut cmd = Command::cargo_bin("sign")?;
+
+    cmd.args([
+        "--mlock",
+        "tests/samples/key",
+        "tests/samples/message",
+        "tmp_signature_sign_mlock",
+    ])
+    .assert()
+    .success();
+    let expected_signature = fs::read("tests/samples/signature")?;
+    let signature = fs::read("tmp_signature_sign_mlock")?;
+    assert_eq!(signature, expected_signature);
+    fs::remove_file("tmp_signature_sign_mlock")?;
+    Ok(())
+}
+
+#[test]
+fn invalid_key_sign() -> TestResult {
+    let mut cmd = Command::cargo_bin("sign")?;
+
+    // secret keys are exactly 32 bytes long
+    fs::write("tmp_invalid_key.sk", [0u8; 33])?;
+    cmd.args([
+        "tmp_invalid_key",
+        "tests/samples/message",
+        "tmp_signature_invalid_key",
+    ])
+    .assert()
+    .failure()
+    .stderr("Invalid key in tmp_invalid_key.sk\n");
+    fs::remove_file("tmp_invalid_key.sk")?;
+    Ok(())
+}
+
 #[test]
 fn correct_verify() -> TestResult {
     let mut cmd = Command::cargo_bin("verify")?;
@@ -90,3 +127,510 @@ fn correct_flow() -> TestResult {
 
     Ok(())
 }
+
+// the message is streamed, in chunks smaller than the file
+#[test]
+fn correct_large_flow() -> TestResult {
+    let message: Vec<u8> = (0..3_000_000u32).map(|i| (i % 251) as u8).collect();
+    fs::write("tmp_large_message", &message)?;
+    Command::cargo_bin("keygen")?
+        .args(["tmp_large_key"])
+        .assert()
+        .success();
+
+    for (flag, sigfile) in [
+        (None, "tmp_large_signature"),
+        (Some("--prehash"), "tmp_large_signature_ph"),
+    ] {
+        Command::cargo_bin("sign")?
+            .args(flag)
+            .args(["tmp_large_key", "tmp_large_message", sigfile])
+            .assert()
+            .success();
+        Command::cargo_bin("verify")?
+            .args(flag)
+            .args(["tmp_large_key.pk", "tmp_large_message", sigfile])
+            .assert()
+            .success()
+            .stdout("ACCEPT\n");
+        Command::cargo_bin("verify")?
+            .args(flag)
+            .args(["tmp_large_key.pk", "tests/samples/message", sigfile])
+            .assert()
+            .success()
+            .stdout("REJECT\n");
+        fs::remove_file(sigfile)?;
+    }
+
+    fs::remove_file("tmp_large_key.sk")?;
+    fs::remove_file("tmp_large_key.pk")?;
+    fs::remove_file("tmp_large_message")?;
+    Ok(())
+}
+
+#[test]
+fn correct_ed448_large_flow() -> TestResult {
+    let message: Vec<u8> = (0..3_000_000u32).map(|i| (i % 251) as u8).collect();
+    fs::write("tmp_ed448_large_message", &message)?;
+    let ed448 = ["--algorithm", "ed448"];
+    Command::cargo_bin("keygen")?
+        .args(ed448)
+        .args(["tmp_ed448_large_key"])
+        .assert()
+        .success();
+    Command::cargo_bin("sign")?
+        .args(ed448)
+        .args([
+            "--context",
+            "foo",
+            "tmp_ed448_large_key",
+            "tmp_ed448_large_message",
+            "tmp_ed448_large_signature",
+        ])
+        .assert()
+        .success();
+    for (datafile, verdict) in [
+        ("tmp_ed448_large_message", "ACCEPT\n"),
+        ("tests/samples/message", "REJECT\n"),
+    ] {
+        Command::cargo_bin("verify")?
+            .args(ed448)
+            .args([
+                "--context",
+                "foo",
+                "tmp_ed448_large_key.pk",
+                datafile,
+                "tmp_ed448_large_signature",
+            ])
+            .assert()
+            .success()
+            .stdout(verdict);
+    }
+
+    fs::remove_file("tmp_ed448_large_signature")?;
+    fs::remove_file("tmp_ed448_large_key.sk")?;
+    fs::remove_file("tmp_ed448_large_key.pk")?;
+    fs::remove_file("tmp_ed448_large_message")?;
+    Ok(())
+}
+
+// RFC 8032, section 7.2
+#[test]
+fn correct_ctx_sign() -> TestResult {
+    let mut cmd = Command::cargo_bin("sign")?;
+
+    cmd.args([
+        "--context",
+        "foo",
+        "tests/samples/ctx_key",
+        "tests/samples/ctx_message",
+        "tmp_signature_ctx_sign",
+    ])
+    .assert()
+    .success();
+    let expected_signature = fs::read("tests/samples/ctx_signature")?;
+    let signature = fs::read("tmp_signature_ctx_sign")?;
+    assert_eq!(signature, expected_signature);
+    fs::remove_file("tmp_signature_ctx_sign")?;
+    Ok(())
+}
+
+#[test]
+fn correct_ctx_verify() -> TestResult {
+    let mut cmd = Command::cargo_bin("verify")?;
+
+    cmd.args([
+        "--context",
+        "foo",
+        "tests/samples/ctx_key.pk",
+        "tests/samples/ctx_message",
+        "tests/samples/ctx_signature",
+    ])
+    .assert()
+    .success()
+    .stdout("ACCEPT\n");
+    Ok(())
+}
+
+#[test]
+fn wrong_context_verify() -> TestResult {
+    let mut cmd = Command::cargo_bin("verify")?;
+
+    cmd.args([
+        "--context",
+        "bar",
+        "tests/samples/ctx_key.pk",
+        "tests/samples/ctx_message",
+        "tests/samples/ctx_signature",
+    ])
+    .assert()
+    .success()
+    .stdout("REJECT\n");
+    Ok(())
+}
+
+#[test]
+fn long_context_sign() -> TestResult {
+    let mut cmd = Command::cargo_bin("sign")?;
+
+    cmd.args([
+        "--context",
+        &"a".repeat(256),
+        "tests/samples/ctx_key",
+        "tests/samples/ctx_message",
+        "tmp_signature_long_context",
+    ])
+    .assert()
+    .failure()
+    .stderr("The context is longer than 255 bytes\n");
+    Ok(())
+}
+
+// RFC 8032, section 7.3
+#[test]
+fn correct_ph_sign() -> TestResult {
+    let mut cmd = Command::cargo_bin("sign")?;
+
+    cmd.args([
+        "--prehash",
+        "tests/samples/ph_key",
+        "tests/samples/ph_message",
+        "tmp_signature_ph_sign",
+    ])
+    .assert()
+    .success();
+    let expected_signature = fs::read("tests/samples/ph_signature")?;
+    let signature = fs::read("tmp_signature_ph_sign")?;
+    assert_eq!(signature, expected_signature);
+    fs::remove_file("tmp_signature_ph_sign")?;
+    Ok(())
+}
+
+#[test]
+fn correct_ph_verify() -> TestResult {
+    let mut cmd = Command::cargo_bin("verify")?;
+
+    cmd.args([
+        "--prehash",
+        "tests/samples/ph_key.pk",
+        "tests/samples/ph_message",
+        "tests/samples/ph_signature",
+    ])
+    .assert()
+    .success()
+    .stdout("ACCEPT\n");
+
+    // the same signature is not a pure Ed25519 one
+    let mut cmd = Command::cargo_bin("verify")?;
+    cmd.args([
+        "tests/samples/ph_key.pk",
+        "tests/samples/ph_message",
+        "tests/samples/ph_signature",
+    ])
+    .assert()
+    .success()
+    .stdout("REJECT\n");
+    Ok(())
+}
+
+// "Taming the many EdDSAs", test vector 4: only the cofactored equation holds
+fn taming4_verify(
+    policy: Option<&str>,
+) -> Result<assert_cmd::assert::Assert, Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("verify")?;
+    if let Some(policy) = policy {
+        cmd.args(["--policy", policy]);
+    }
+    Ok(cmd
+        .args([
+            "tests/samples/taming4.pk",
+            "tests/samples/taming4_message",
+            "tests/samples/taming4_signature",
+        ])
+        .assert())
+}
+
+#[test]
+fn policy_verify() -> TestResult {
+    taming4_verify(None)?.success().stdout("REJECT\n");
+    taming4_verify(Some("strict"))?.success().stdout("REJECT\n");
+    taming4_verify(Some("no-small-order-keys"))?
+        .success()
+        .stdout("REJECT\n");
+    taming4_verify(Some("cofactored"))?
+        .success()
+        .stdout("ACCEPT\n");
+    taming4_verify(Some("zip215"))?.success().stdout("ACCEPT\n");
+    Ok(())
+}
+
+#[test]
+fn explain_verify() -> TestResult {
+    let explain = |args: &[&str]| -> Result<_, Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("verify")?;
+        Ok(cmd.arg("--explain").args(args).assert().success())
+    };
+
+    explain(&[
+        "tests/samples/key.pk",
+        "tests/samples/message",
+        "tests/samples/signature",
+    ])?
+    .stdout("ACCEPT\n");
+    explain(&[
+        "tests/samples/taming4.pk",
+        "tests/samples/taming4_message",
+        "tests/samples/taming4_signature",
+    ])?
+    .stdout("REJECT: the verification equation does not hold\n");
+
+    // s = 2^253 - 1 >= L
+    let mut signature = fs::read("tests/samples/signature")?;
+    signature[32..].copy_from_slice(&[0xff; 32]);
+    signature[63] = 0x1f;
+    fs::write("tmp_signature_explain", signature)?;
+    explain(&[
+        "tests/samples/key.pk",
+        "tests/samples/message",
+        "tmp_signature_explain",
+    ])?
+    .stdout("REJECT: invalid signature: s is out of range (s >= L)\n");
+    fs::remove_file("tmp_signature_explain")?;
+    Ok(())
+}
+
+#[test]
+fn policy_ed448_verify() -> TestResult {
+    let mut cmd = Command::cargo_bin("verify")?;
+
+    cmd.args([
+        "--algorithm",
+        "ed448",
+        "--policy",
+        "cofactored",
+        "tests/samples/ed448_key.pk",
+        "tests/samples/ed448_message",
+        "tests/samples/ed448_signature",
+    ])
+    .assert()
+    .failure()
+    .stderr("--policy is only supported with ed25519\n");
+    Ok(())
+}
+
+#[test]
+fn correct_ed448_sign() -> TestResult {
+    let mut cmd = Command::cargo_bin("sign")?;
+
+    cmd.args([
+        "--algorithm",
+        "ed448",
+        "tests/samples/ed448_key",
+        "tests/samples/ed448_message",
+        "tmp_signature_ed448_sign",
+    ])
+    .assert()
+    .success();
+    let expected_signature = fs::read("tests/samples/ed448_signature")?;
+    let signature = fs::read("tmp_signature_ed448_sign")?;
+    assert_eq!(signature, expected_signature);
+    fs::remove_file("tmp_signature_ed448_sign")?;
+    Ok(())
+}
+
+#[test]
+fn correct_ed448_verify() -> TestResult {
+    let mut cmd = Command::cargo_bin("verify")?;
+
+    cmd.args([
+        "--algorithm",
+        "ed448",
+        "tests/samples/ed448_key.pk",
+        "tests/samples/ed448_message",
+        "tests/samples/ed448_signature",
+    ])
+    .assert()
+    .success()
+    .stdout("ACCEPT\n");
+    Ok(())
+}
+
+#[test]
+fn invalid_key_ed448_verify() -> TestResult {
+    let mut cmd = Command::cargo_bin("verify")?;
+
+    // Ed448 public keys are 57 bytes long
+    cmd.args([
+        "--algorithm",
+        "ed448",
+        "tests/samples/key.pk",
+        "tests/samples/ed448_message",
+        "tests/samples/ed448_signature",
+    ])
+    .assert()
+    .failure()
+    .stderr("Invalid key in tests/samples/key.pk\n");
+    Ok(())
+}
+
+#[test]
+fn correct_ed448_flow() -> TestResult {
+    let mut keygen = Command::cargo_bin("keygen")?;
+    let mut sign = Command::cargo_bin("sign")?;
+    let mut verify = Command::cargo_bin("verify")?;
+
+    keygen
+        .args(["--algorithm", "ed448", "tmp_ed448_key"])
+        .assert()
+        .success();
+    assert_eq!(fs::read("tmp_ed448_key.sk")?.len(), 57);
+
+    sign.args([
+        "--algorithm",
+        "ed448",
+        "tmp_ed448_key",
+        "tests/samples/message",
+        "tmp_signature_ed448_flow",
+    ])
+    .assert()
+    .success();
+
+    verify
+        .args([
+            "--algorithm",
+            "ed448",
+            "tmp_ed448_key.pk",
+            "tests/samples/message",
+            "tmp_signature_ed448_flow",
+        ])
+        .assert()
+        .success()
+        .stdout("ACCEPT\n");
+
+    fs::remove_file("tmp_ed448_key.sk")?;
+    fs::remove_file("tmp_ed448_key.pk")?;
+    fs::remove_file("tmp_signature_ed448_flow")?;
+
+    Ok(())
+}
+
+#[test]
+fn correct_x25519_exchange() -> TestResult {
+    let mut cmd = Command::cargo_bin("x25519")?;
+
+    cmd.args([
+        "exchange",
+        "tests/samples/x25519_alice.sk",
+        "tests/samples/x25519_bob.pk",
+        "tmp_shared_exchange",
+    ])
+    .assert()
+    .success();
+    let expected_shared = fs::read("tests/samples/x25519_shared")?;
+    let shared = fs::read("tmp_shared_exchange")?;
+    assert_eq!(shared, expected_shared);
+    fs::remove_file("tmp_shared_exchange")?;
+    Ok(())
+}
+
+#[test]
+fn zero_x25519_exchange() -> TestResult {
+    let mut cmd = Command::cargo_bin("x25519")?;
+
+    cmd.args([
+        "exchange",
+        "tests/samples/x25519_alice.sk",
+        "tests/samples/x25519_zero.pk",
+        "tmp_shared_zero",
+    ])
+    .assert()
+    .failure();
+    Ok(())
+}
+
+#[test]
+fn correct_x25519_flow() -> TestResult {
+    for prefix in ["tmp_x25519_alice", "tmp_x25519_bob"] {
+        Command::cargo_bin("x25519")?
+            .args(["keygen", prefix])
+            .assert()
+            .success();
+    }
+
+    for (sk, pk, shared) in [
+        (
+            "tmp_x25519_alice.sk",
+            "tmp_x25519_bob.pk",
+            "tmp_shared_alice",
+        ),
+        ("tmp_x25519_bob.sk", "tmp_x25519_alice.pk", "tmp_shared_bob"),
+    ] {
+        Command::cargo_bin("x25519")?
+            .args(["exchange", sk, pk, shared])
+            .assert()
+            .success();
+    }
+    assert_eq!(fs::read("tmp_shared_alice")?, fs::read("tmp_shared_bob")?);
+
+    for filename in [
+        "tmp_x25519_alice.sk",
+        "tmp_x25519_alice.pk",
+        "tmp_x25519_bob.sk",
+        "tmp_x25519_bob.pk",
+        "tmp_shared_alice",
+        "tmp_shared_bob",
+    ] {
+        fs::remove_file(filename)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn correct_x25519_from_ed25519() -> TestResult {
+    for (subcommand, infile, expected) in [
+        (
+            "pk-from-ed25519",
+            "tests/samples/ed25519_convert.pk",
+            "tests/samples/x25519_convert.pk",
+        ),
+        (
+            "sk-from-ed25519",
+            "tests/samples/ed25519_convert.sk",
+            "tests/samples/x25519_convert.sk",
+        ),
+    ] {
+        Command::cargo_bin("x25519")?
+            .args([subcommand, infile, "tmp_key_convert"])
+            .assert()
+            .success();
+        assert_eq!(fs::read("tmp_key_convert")?, fs::read(expected)?);
+        fs::remove_file("tmp_key_convert")?;
+    }
+    Ok(())
+}
+
+#[test]
+fn invalid_x25519_from_ed25519() -> TestResult {
+    let mut cmd = Command::cargo_bin("x25519")?;
+
+    cmd.args([
+        "pk-from-ed25519",
+        "tests/samples/x25519_zero.pk",
+        "tmp_key_invalid_convert",
+    ])
+    .assert()
+    .failure();
+    Ok(())
+}
+
+#[test]
+fn invalid_dudect_measurements() -> TestResult {
+    // the percentiles need at least 2 measurements
+    for n in ["0", "1"] {
+        Command::cargo_bin("dudect")?
+            .args(["-n", n])
+            .assert()
+            .failure();
+    }
+    Ok(())
+}